@@ -0,0 +1,80 @@
+use uom::si::f64::{Length, Time};
+use uom::si::{length::meter, time::second};
+use crate::{Path, Pose};
+
+fn translation_between(a: &Pose, b: &Pose) -> f64 {
+    (b.x - a.x).get::<meter>().hypot((b.y - a.y).get::<meter>())
+}
+
+impl Path {
+    /// (time, distance travelled) at every sample, integrating the translation between samples
+    fn distance_table(&self) -> Vec<(f64, f64)> {
+        let mut table = Vec::with_capacity(self.samples.len());
+        let mut distance = 0.;
+        let mut previous: Option<&Pose> = None;
+        for (t, pose) in &self.samples {
+            if let Some(previous) = previous {
+                distance += translation_between(previous, pose);
+            }
+            table.push((**t, distance));
+            previous = Some(pose);
+        }
+        table
+    }
+
+    /// Distance travelled over the whole path
+    pub fn total_distance(&self) -> Length {
+        Length::new::<meter>(self.distance_table().last().map_or(0., |&(_, distance)| distance))
+    }
+
+    /// Distance travelled by `elapsed`, clamped to the path
+    pub fn distance_at(&self, elapsed: Time) -> Length {
+        let table = self.distance_table();
+        let elapsed = elapsed.get::<second>();
+        let Some(i) = table.iter().rposition(|&(t, _)| t <= elapsed) else {
+            return Length::new::<meter>(0.);
+        };
+
+        let (t, distance) = table[i];
+        let sample = self.get(Time::new::<second>(t));
+        let pose = self.get(Time::new::<second>(elapsed));
+        let distance = match table.get(i + 1) {
+            Some(&(_, next)) => (distance + translation_between(&sample, &pose)).min(next),
+            None => distance,
+        };
+        Length::new::<meter>(distance)
+    }
+
+    /// The first time the robot has travelled `distance`, clamped to the path
+    pub fn time_at_distance(&self, distance: Length) -> Time {
+        let table = self.distance_table();
+        let distance = distance.get::<meter>();
+        let Some(end) = table.iter().position(|&(_, d)| d >= distance) else {
+            return self.length();
+        };
+        if end == 0 {
+            return Time::new::<second>(table[0].0);
+        }
+
+        let (start_time, start_distance) = table[end - 1];
+        let (end_time, end_distance) = table[end];
+        let progress = (distance - start_distance) / (end_distance - start_distance);
+        Time::new::<second>(start_time + (end_time - start_time) * progress)
+    }
+
+    /// The pose once the robot has travelled `distance`
+    pub fn get_at_distance(&self, distance: Length) -> Pose {
+        self.get(self.time_at_distance(distance))
+    }
+}
+
+#[test]
+fn distance_parameterization() {
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+
+    assert_eq!(path.total_distance().get::<meter>(), 2.);
+    assert_eq!(path.distance_at(Time::new::<second>(0.5)).get::<meter>(), 0.5);
+    assert_eq!(path.time_at_distance(Length::new::<meter>(1.5)).get::<second>(), 1.5);
+    assert_eq!(path.get_at_distance(Length::new::<meter>(1.5)).x.get::<meter>(), 2.5);
+    assert_eq!(path.time_at_distance(Length::new::<meter>(10.)), path.length());
+}
@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// Why a trajectory file couldn't be turned into a `Path`
+#[derive(Debug)]
+pub enum TrajectoryError {
+    Parse(serde_json::Error),
+    /// A sample or waypoint field is NaN or infinite
+    NonFinite { index: usize, field: &'static str },
+    /// The trajectory has no samples
+    Empty,
+    /// A sample's timestamp is earlier than the one before it
+    NonMonotonic { index: usize, time: f64, previous: f64 },
+    /// Two samples share a timestamp
+    DuplicateTime { index: usize, time: f64 },
+    /// The number of waypoints doesn't match the number of waypoint timestamps
+    WaypointMismatch { waypoints: usize, timestamps: usize },
+    /// A split starts at a sample that doesn't exist
+    SplitOutOfRange { split: usize, samples: usize },
+    /// An event targets a waypoint that doesn't exist and has no timestamp of its own
+    UnresolvedEvent { index: usize },
+    /// The trajectory's `sampleType` isn't one this crate knows
+    UnknownSampleType(String),
+    /// A sample's fields don't match the trajectory's `sampleType`
+    SampleTypeMismatch { index: usize, sample_type: &'static str },
+}
+
+impl fmt::Display for TrajectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrajectoryError::Parse(err) => write!(f, "failed to parse trajectory: {}", err),
+            TrajectoryError::NonFinite { index, field } => write!(f, "{} of sample {} is not finite", field, index),
+            TrajectoryError::Empty => write!(f, "trajectory has no samples"),
+            TrajectoryError::NonMonotonic { index, time, previous } => write!(f, "sample {} at {} s comes after a sample at {} s", index, time, previous),
+            TrajectoryError::DuplicateTime { index, time } => write!(f, "sample {} has the same timestamp as the one before it ({} s)", index, time),
+            TrajectoryError::WaypointMismatch { waypoints, timestamps } => write!(f, "{} waypoints but {} waypoint timestamps", waypoints, timestamps),
+            TrajectoryError::SplitOutOfRange { split, samples } => write!(f, "split at sample {} but there are only {} samples", split, samples),
+            TrajectoryError::UnresolvedEvent { index } => write!(f, "event {} targets a missing waypoint and has no timestamp", index),
+            TrajectoryError::UnknownSampleType(sample_type) => write!(f, "unknown sample type {:?}", sample_type),
+            TrajectoryError::SampleTypeMismatch { index, sample_type } => write!(f, "sample {} is not a {} sample", index, sample_type),
+        }
+    }
+}
+
+impl std::error::Error for TrajectoryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TrajectoryError::Parse(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for TrajectoryError {
+    fn from(value: serde_json::Error) -> Self {
+        TrajectoryError::Parse(value)
+    }
+}
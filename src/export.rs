@@ -0,0 +1,374 @@
+use serde_json::{json, Value};
+use uom::si::f64::{Acceleration, Angle, AngularAcceleration, AngularVelocity, Length, Velocity};
+use uom::si::{length::meter, angle::radian, time::second};
+use crate::{Constraint, DifferentialSample, DriveType, Event, Path, Pose, Sample};
+
+/// Values closer than this are considered unchanged when comparing against the source file
+const EPSILON: f64 = 1e-9;
+
+impl Path {
+    /// Serialize as a Choreo .traj file. A path loaded from a .traj file, or derived from one with
+    /// `reversed`, `flipped`, `transformed` or `time_scaled`, is written in the same schema,
+    /// keeping every field this crate doesn't model, like event commands and the sample type.
+    /// Other paths are written as 2025 files
+    pub fn to_trajectory(&self, name: &str) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.to_choreo(name)?)
+    }
+
+    /// The .traj file as JSON. See `to_trajectory`
+    pub fn to_choreo(&self, name: &str) -> Result<Value, serde_json::Error> {
+        let mut file = match &self.source {
+            Some(source) => source.clone(),
+            None => self.choreo_skeleton(),
+        };
+        let is_2025 = file["version"].is_string();
+        let waypoint_times = self.all_waypoint_times();
+
+        file["name"] = json!(name);
+        let differential = file["trajectory"]["sampleType"] == "DifferentialDrive";
+        file["trajectory"]["samples"] = Value::Array(self.choreo_samples(&file["trajectory"]["samples"], differential)?);
+        file["trajectory"]["waypoints"] = json!(waypoint_times);
+        if is_2025 {
+            file["trajectory"]["splits"] = json!(self.split_indices());
+        }
+        file["events"] = Value::Array(self.choreo_events(&file["events"], &waypoint_times, is_2025));
+        self.choreo_constraints(&mut file)?;
+
+        Ok(file)
+    }
+
+    /// Write the path's constraints to the snapshot and params, unless they are all unchanged
+    /// from the file. Changed constraints lose their params expressions
+    fn choreo_constraints(&self, file: &mut Value) -> Result<(), serde_json::Error> {
+        let list = |pointer| file.pointer(pointer).and_then(Value::as_array).cloned().unwrap_or_default();
+        let (snapshot, params) = (list("/snapshot/constraints"), list("/params/constraints"));
+        let unchanged: Vec<bool> = self.constraints.iter().enumerate().map(|(i, constraint)| {
+            snapshot.get(i)
+                .and_then(|original| serde_json::from_value::<Constraint>(original.clone()).ok())
+                .is_some_and(|original| &original == constraint)
+        }).collect();
+        if snapshot.len() == self.constraints.len() && params.len() == snapshot.len() && unchanged.iter().all(|&same| same) {
+            return Ok(());
+        }
+
+        let mut new_snapshot = Vec::new();
+        let mut new_params = Vec::new();
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            let (snapshot_constraint, params_constraint) = match params.get(i) {
+                Some(params) if unchanged[i] => (snapshot[i].clone(), params.clone()),
+                _ => {
+                    let constraint = serde_json::to_value(constraint)?;
+                    (constraint.clone(), params_constraint(constraint))
+                }
+            };
+            new_snapshot.push(snapshot_constraint);
+            new_params.push(params_constraint);
+        }
+        file["snapshot"]["constraints"] = Value::Array(new_snapshot);
+        file["params"]["constraints"] = Value::Array(new_params);
+        Ok(())
+    }
+
+    /// Move the source file's waypoints through `pose`, so a transformed path saves its
+    /// waypoints where its samples now are. Moved params waypoints lose their expressions
+    pub(crate) fn map_source_waypoints(&mut self, pose: impl Fn(&Pose) -> Pose) {
+        let Some(source) = &mut self.source else {
+            return;
+        };
+        let mut moved = Vec::new();
+        for waypoint in source.pointer_mut("/snapshot/waypoints").and_then(Value::as_array_mut).into_iter().flatten() {
+            let (Some(x), Some(y), Some(heading)) = (waypoint["x"].as_f64(), waypoint["y"].as_f64(), waypoint["heading"].as_f64()) else {
+                moved.push(None);
+                continue;
+            };
+            let pose = pose(&waypoint_pose(x, y, heading));
+            let (x, y, heading) = (pose.x.get::<meter>(), pose.y.get::<meter>(), pose.heading.get::<radian>());
+            (waypoint["x"], waypoint["y"], waypoint["heading"]) = (json!(x), json!(y), json!(heading));
+            moved.push(Some((x, y, heading)));
+        }
+        let params = source.pointer_mut("/params/waypoints").and_then(Value::as_array_mut).into_iter().flatten();
+        for (waypoint, moved) in params.zip(moved) {
+            if let Some((x, y, heading)) = moved {
+                waypoint["x"] = json!({ "exp": format!("{} m", x), "val": x });
+                waypoint["y"] = json!({ "exp": format!("{} m", y), "val": y });
+                waypoint["heading"] = json!({ "exp": format!("{} rad", heading), "val": heading });
+            }
+        }
+    }
+
+    /// Reverse the order of the source file's waypoints, for a reversed path. A waypoint's
+    /// `intervals` counts the samples to the next one, so they shift along by one
+    pub(crate) fn reverse_source_waypoints(&mut self) {
+        let Some(source) = &mut self.source else {
+            return;
+        };
+        for pointer in ["/snapshot/waypoints", "/params/waypoints"] {
+            let Some(waypoints) = source.pointer_mut(pointer).and_then(Value::as_array_mut) else {
+                continue;
+            };
+            let intervals: Vec<Value> = waypoints.iter().map(|waypoint| waypoint["intervals"].clone()).collect();
+            let count = waypoints.len();
+            waypoints.reverse();
+            for (i, waypoint) in waypoints.iter_mut().enumerate() {
+                if waypoint.get("intervals").is_some() {
+                    waypoint["intervals"] = intervals[if i + 1 < count { count - 2 - i } else { i }].clone();
+                }
+            }
+        }
+    }
+
+    /// Timestamp of every waypoint. Paths that don't track them get the start, the split
+    /// waypoints and the end
+    fn all_waypoint_times(&self) -> Vec<f64> {
+        if !self.waypoint_times.is_empty() {
+            return self.waypoint_times.clone();
+        }
+        let (Some(first), Some(last)) = (self.samples.first_key_value(), self.samples.last_key_value()) else {
+            return Vec::new();
+        };
+        let mut times = vec![**first.0];
+        times.extend(&self.waypoints);
+        times.push(**last.0);
+        times
+    }
+
+    /// Index of the first sample of every split
+    fn split_indices(&self) -> Vec<usize> {
+        let times: Vec<f64> = self.samples.keys().map(|t| **t).collect();
+        let mut splits = vec![0];
+        for &waypoint in &self.waypoints {
+            let nearest = (0..times.len())
+                .min_by(|&a, &b| (times[a] - waypoint).abs().total_cmp(&(times[b] - waypoint).abs()));
+            if let Some(nearest) = nearest.filter(|&i| i != 0) {
+                splits.push(nearest);
+            }
+        }
+        splits
+    }
+
+    /// Samples from the path, as differential drive samples if `differential`. Where a sample
+    /// matches the source file, the original is kept so fields like module forces survive a round
+    /// trip
+    fn choreo_samples(&self, original: &Value, differential: bool) -> Result<Vec<Value>, serde_json::Error> {
+        let original = original.as_array().map(Vec::as_slice).unwrap_or_default();
+        // Keep the source's schema: 2024 files have no accelerations
+        let accelerations = differential || original.first().is_none_or(|sample| sample.get("ax").is_some());
+        let half_track_width = self.half_track_width(original);
+        self.samples.iter().enumerate().map(|(i, (t, pose))| {
+            let mut sample = match differential {
+                true => serde_json::to_value(DifferentialSample::from_pose(**t, pose, half_track_width))?,
+                false => serde_json::to_value(Sample::from_pose(**t, pose))?,
+            };
+            if !accelerations {
+                for key in ["ax", "ay", "alpha"] {
+                    sample.as_object_mut().unwrap().remove(key);
+                }
+            }
+            Ok(match original.get(i) {
+                Some(original) if same_values(&sample, original) => original.clone(),
+                _ => sample,
+            })
+        }).collect()
+    }
+
+    /// Half the distance between a differential drive's wheels, from the robot config or else
+    /// from the wheel speeds of a turning sample in the source file. Zero if neither is known
+    fn half_track_width(&self, original: &[Value]) -> f64 {
+        let from_params = self.params.as_ref()
+            .filter(|params| params.drive_type == DriveType::Differential)
+            .and_then(|params| params.modules.first())
+            .map(|module| module.y.get::<meter>().abs());
+        from_params.or_else(|| original.iter().find_map(|sample| {
+            let (left, right, omega) = (sample["vl"].as_f64()?, sample["vr"].as_f64()?, sample["omega"].as_f64()?);
+            (omega.abs() > EPSILON).then(|| (right - left) / (2. * omega))
+        })).unwrap_or(0.)
+    }
+
+    /// Events from the path. Each reuses the source event with the same name, so its command is
+    /// kept, and only gets new timing if it no longer resolves to the marker's timestamp
+    fn choreo_events(&self, original: &Value, waypoint_times: &[f64], is_2025: bool) -> Vec<Value> {
+        let mut original = original.as_array().cloned().unwrap_or_default();
+        self.events.iter().map(|marker| {
+            let timestamp = marker.timestamp.get::<second>();
+            let mut event = match original.iter().position(|event| event["name"] == json!(marker.name)) {
+                Some(i) => original.remove(i),
+                None => json!({ "name": marker.name, "event": null }),
+            };
+
+            let resolved = serde_json::from_value::<Event>(event.clone())
+                .ok()
+                .and_then(|event| event.timestamp(waypoint_times));
+            if !resolved.is_some_and(|resolved| (resolved - timestamp).abs() < EPSILON) {
+                let offset = if is_2025 { json!({ "exp": "0 s", "val": 0.0 }) } else { json!(0.0) };
+                event["from"] = json!({ "target": null, "targetTimestamp": timestamp, "offset": offset });
+            }
+            event
+        }).collect()
+    }
+
+    /// A 2025 file for a path that wasn't loaded from Choreo
+    fn choreo_skeleton(&self) -> Value {
+        let times = self.all_waypoint_times();
+        let sample_times: Vec<f64> = self.samples.keys().map(|t| **t).collect();
+        let target_dt = match (sample_times.first(), sample_times.last()) {
+            (Some(first), Some(last)) if sample_times.len() > 1 => (last - first) / (sample_times.len() - 1) as f64,
+            _ => 0.05,
+        };
+
+        let mut snapshot_waypoints = Vec::new();
+        let mut params_waypoints = Vec::new();
+        for (i, &t) in times.iter().enumerate() {
+            let pose = self.get(uom::si::f64::Time::new::<second>(t));
+            let (x, y, heading) = (pose.x.get::<meter>(), pose.y.get::<meter>(), pose.heading.get::<radian>());
+            // Choreo's default for the last waypoint, which has no segment after it
+            let intervals = match times.get(i + 1) {
+                Some(&next) => sample_times.iter().filter(|&&sample| t <= sample && sample < next).count(),
+                None => 40,
+            };
+            let split = self.waypoints.iter().any(|&waypoint| (waypoint - t).abs() < EPSILON);
+            let flags = json!({ "intervals": intervals, "split": split, "fixTranslation": true, "fixHeading": true, "overrideIntervals": false });
+
+            let mut snapshot = json!({ "x": x, "y": y, "heading": heading });
+            let mut params = json!({
+                "x": { "exp": format!("{} m", x), "val": x },
+                "y": { "exp": format!("{} m", y), "val": y },
+                "heading": { "exp": format!("{} rad", heading), "val": heading },
+            });
+            for (key, value) in flags.as_object().unwrap() {
+                snapshot[key] = value.clone();
+                params[key] = value.clone();
+            }
+            snapshot_waypoints.push(snapshot);
+            params_waypoints.push(params);
+        }
+
+        let constraints = json!(self.constraints);
+        let params_constraints: Vec<Value> = constraints.as_array().into_iter().flatten().cloned().map(params_constraint).collect();
+
+        // Differential samples need the track width, which only the robot config gives here
+        let sample_type = match &self.params {
+            Some(params) if params.drive_type == DriveType::Differential && !params.modules.is_empty() => "DifferentialDrive",
+            _ => "Swerve",
+        };
+
+        json!({
+            "name": "",
+            "version": "v2025.0.0",
+            "snapshot": { "waypoints": snapshot_waypoints, "constraints": constraints, "targetDt": target_dt },
+            "params": {
+                "waypoints": params_waypoints,
+                "constraints": params_constraints,
+                "targetDt": { "exp": format!("{} s", target_dt), "val": target_dt },
+            },
+            "trajectory": { "sampleType": sample_type, "waypoints": [], "samples": [], "splits": [] },
+            "events": [],
+        })
+    }
+}
+
+/// A waypoint position and heading as a stopped pose
+fn waypoint_pose(x: f64, y: f64, heading: f64) -> Pose {
+    Pose {
+        x: Length::new::<meter>(x),
+        y: Length::new::<meter>(y),
+        heading: Angle::new::<radian>(heading),
+        angular_velocity: AngularVelocity::default(),
+        velocity_x: Velocity::default(),
+        velocity_y: Velocity::default(),
+        angular_acceleration: AngularAcceleration::default(),
+        acceleration_x: Acceleration::default(),
+        acceleration_y: Acceleration::default(),
+    }
+}
+
+/// A snapshot constraint in the params layout, with its numbers as unit expressions
+fn params_constraint(mut constraint: Value) -> Value {
+    let kind = constraint["data"]["type"].as_str().unwrap_or_default().to_owned();
+    for (key, value) in constraint["data"]["props"].as_object_mut().into_iter().flatten() {
+        if let (Some(val), Some(unit)) = (value.as_f64(), constraint_unit(&kind, key)) {
+            *value = json!({ "exp": format!("{} {}", val, unit), "val": val });
+        }
+    }
+    constraint
+}
+
+/// Unit of a numeric constraint property in Choreo's expression syntax
+fn constraint_unit(kind: &str, key: &str) -> Option<&'static str> {
+    match (kind, key) {
+        ("MaxVelocity", "max") => Some("m / s"),
+        ("MaxAngularVelocity", "max") => Some("rad / s"),
+        (_, "direction" | "tolerance") => Some("rad"),
+        (_, "x" | "y" | "w" | "h" | "r") => Some("m"),
+        _ => None,
+    }
+}
+
+/// Whether every number in `new` has the same value in `original`
+fn same_values(new: &Value, original: &Value) -> bool {
+    new.as_object().is_some_and(|new| new.iter().all(|(key, value)| match (value.as_f64(), original[key].as_f64()) {
+        (Some(a), Some(b)) => (a - b).abs() < EPSILON,
+        _ => value == &original[key],
+    }))
+}
+
+#[test]
+fn choreo_round_trip() {
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let saved = path.to_choreo("Test").unwrap();
+
+    assert_eq!(saved, serde_json::from_str::<Value>(crate::TEST_TRAJ_2025).unwrap());
+}
+
+#[test]
+fn choreo_differential() {
+    let data = crate::TEST_TRAJ_2025.replace(r#""sampleType": "Swerve""#, r#""sampleType": "DifferentialDrive""#);
+    let mut file: Value = serde_json::from_str(&data).unwrap();
+    file["trajectory"]["samples"] = json!([
+        {"t": 0.0, "x": 1.0, "y": 1.0, "heading": 0.0, "vl": 0.0, "vr": 0.0, "omega": 0.0, "al": 0.5, "ar": 1.5, "alpha": 2.5, "fl": 0.0, "fr": 0.0},
+        {"t": 1.0, "x": 2.0, "y": 1.0, "heading": 0.0, "vl": 0.8, "vr": 1.2, "omega": 1.0, "al": 0.0, "ar": 0.0, "alpha": 0.0, "fl": 0.0, "fr": 0.0},
+        {"t": 2.0, "x": 3.0, "y": 1.0, "heading": 0.0, "vl": 0.0, "vr": 0.0, "omega": 0.0, "al": 0.0, "ar": 0.0, "alpha": 0.0, "fl": 0.0, "fr": 0.0},
+    ]);
+    let path = Path::from_trajectory(&file.to_string()).unwrap();
+    let pose = path.get(uom::si::f64::Time::new::<second>(1.));
+    assert_eq!(pose.velocity_x.get::<uom::si::velocity::meter_per_second>(), 1.);
+    assert_eq!(path.to_choreo("Test").unwrap(), file);
+
+    file["trajectory"]["samples"][0] = json!({"t": 0.0, "x": 1.0, "y": 1.0, "heading": 0.0, "vx": 0.0, "vy": 0.0, "omega": 0.0});
+    assert!(matches!(Path::from_trajectory(&file.to_string()), Err(crate::TrajectoryError::SampleTypeMismatch { index: 0, .. })));
+}
+
+#[test]
+fn choreo_flipped_keeps_source() {
+    let data = crate::TEST_TRAJ_2025.replace(r#""sampleType": "Swerve""#, r#""sampleType": "DifferentialDrive""#);
+    let mut file: Value = serde_json::from_str(&data).unwrap();
+    file["trajectory"]["samples"] = json!([
+        {"t": 0.0, "x": 1.0, "y": 1.0, "heading": 0.0, "vl": 0.0, "vr": 0.0, "omega": 0.0, "al": 0.0, "ar": 0.0, "alpha": 0.0, "fl": 0.0, "fr": 0.0},
+        {"t": 1.0, "x": 2.0, "y": 1.0, "heading": 0.0, "vl": 1.0, "vr": 1.0, "omega": 0.0, "al": 0.0, "ar": 0.0, "alpha": 0.0, "fl": 0.0, "fr": 0.0},
+        {"t": 2.0, "x": 3.0, "y": 1.0, "heading": 0.0, "vl": 0.0, "vr": 0.0, "omega": 0.0, "al": 0.0, "ar": 0.0, "alpha": 0.0, "fl": 0.0, "fr": 0.0},
+    ]);
+    let command = json!({"type": "named", "data": {"name": "intake"}});
+    file["events"][0]["event"] = command.clone();
+    let field = crate::Field::season(2025).unwrap();
+    let path = Path::from_trajectory(&file.to_string()).unwrap().flipped(&field, field.symmetry);
+
+    let saved = path.to_choreo("Test").unwrap();
+    let reloaded = Path::from_trajectory(&saved.to_string()).unwrap();
+    assert_eq!(saved["trajectory"]["sampleType"], "DifferentialDrive");
+    assert_eq!(saved["events"][0]["event"], command);
+    assert!((saved["snapshot"]["waypoints"][0]["x"].as_f64().unwrap() - 16.548).abs() < 1e-9);
+    assert_eq!(reloaded.events().len(), 1);
+    assert!((reloaded.get(uom::si::f64::Time::default()).x.get::<meter>() - 16.548).abs() < 1e-9);
+}
+
+#[test]
+fn choreo_from_other_format() {
+    let data = r#"[
+        {"time": 0.0, "velocity": 0.0, "acceleration": 1.0, "pose": {"translation": {"x": 0.0, "y": 0.0}, "rotation": {"radians": 0.0}}, "curvature": 0.0},
+        {"time": 1.0, "velocity": 1.0, "acceleration": 0.0, "pose": {"translation": {"x": 0.5, "y": 0.0}, "rotation": {"radians": 0.0}}, "curvature": 0.0}
+    ]"#;
+    let path = Path::from_wpilib(data).unwrap();
+    let saved = Path::from_trajectory(&path.to_trajectory("Wpilib").unwrap()).unwrap();
+
+    assert_eq!(saved.length(), path.length());
+    assert_eq!(saved.get(saved.length()).x, path.get(path.length()).x);
+}
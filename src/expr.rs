@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use uom::si::f64::{AngularVelocity, Length, Angle, Velocity, Time};
+use uom::si::{length::meter, angle::radian, velocity::meter_per_second,
+              angular_velocity::radian_per_second, time::second};
+
+/// A Choreo unit expression, e.g. `{"exp": "1.5 m", "val": 1.5}`. `val` is in SI units and is
+/// only a cache of `exp`; it may be missing or stale when variables change
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Expr {
+    pub exp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub val: Option<f64>,
+}
+
+impl Expr {
+    pub fn new(exp: impl Into<String>) -> Self {
+        Self { exp: exp.into(), val: None }
+    }
+
+    /// Evaluate `exp`, resolving names against `variables`
+    pub fn evaluate(&self, variables: &Variables) -> Result<Quantity, ExprError> {
+        evaluate(&self.exp, variables, 0)
+    }
+
+    /// Evaluate `exp` as a quantity of `dimension`, in SI units. A bare number is taken to already
+    /// be in SI units. If `exp` can't be evaluated at all, the cached `val` is used instead
+    pub fn evaluate_as(&self, variables: &Variables, dimension: Dimension) -> Result<f64, ExprError> {
+        match self.evaluate(variables) {
+            Ok(value) if value.dimension == Dimension::NONE => Ok(value.value),
+            Ok(value) => value.expect(dimension),
+            Err(err) => self.val.ok_or(err),
+        }
+    }
+
+    /// The SI value of `exp` evaluated without variables, or the cached `val` if that fails. The
+    /// dimension isn't checked; use `evaluate_as` when it is known
+    pub fn value(&self) -> Result<f64, ExprError> {
+        match evaluate(&self.exp, &Variables::default(), 0) {
+            Ok(value) => Ok(value.value),
+            Err(err) => self.val.ok_or(err),
+        }
+    }
+
+    pub fn length(&self, variables: &Variables) -> Result<Length, ExprError> {
+        Ok(Length::new::<meter>(self.evaluate_as(variables, Dimension::LENGTH)?))
+    }
+
+    pub fn angle(&self, variables: &Variables) -> Result<Angle, ExprError> {
+        Ok(Angle::new::<radian>(self.evaluate_as(variables, Dimension::ANGLE)?))
+    }
+
+    pub fn time(&self, variables: &Variables) -> Result<Time, ExprError> {
+        Ok(Time::new::<second>(self.evaluate_as(variables, Dimension::TIME)?))
+    }
+
+    pub fn velocity(&self, variables: &Variables) -> Result<Velocity, ExprError> {
+        Ok(Velocity::new::<meter_per_second>(self.evaluate_as(variables, Dimension::VELOCITY)?))
+    }
+
+    pub fn angular_velocity(&self, variables: &Variables) -> Result<AngularVelocity, ExprError> {
+        Ok(AngularVelocity::new::<radian_per_second>(self.evaluate_as(variables, Dimension::ANGULAR_VELOCITY)?))
+    }
+}
+
+/// Exponents of the base dimensions Choreo expressions can carry
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Dimension {
+    pub length: i8,
+    pub time: i8,
+    pub angle: i8,
+    pub mass: i8,
+}
+
+impl Dimension {
+    pub const NONE: Self = Self { length: 0, time: 0, angle: 0, mass: 0 };
+    pub const LENGTH: Self = Self { length: 1, ..Self::NONE };
+    pub const TIME: Self = Self { time: 1, ..Self::NONE };
+    pub const ANGLE: Self = Self { angle: 1, ..Self::NONE };
+    pub const MASS: Self = Self { mass: 1, ..Self::NONE };
+    pub const VELOCITY: Self = Self { length: 1, time: -1, ..Self::NONE };
+    pub const ANGULAR_VELOCITY: Self = Self { angle: 1, time: -1, ..Self::NONE };
+    pub const FORCE: Self = Self { length: 1, time: -2, angle: 0, mass: 1 };
+
+    fn mul(self, other: Self) -> Result<Self, ExprError> {
+        self.zip(other, i8::checked_add)
+    }
+
+    fn powi(self, n: i8) -> Result<Self, ExprError> {
+        self.zip(self, |exponent, _| exponent.checked_mul(n))
+    }
+
+    /// Combine the exponents pairwise, failing if one no longer fits
+    fn zip(self, other: Self, f: impl Fn(i8, i8) -> Option<i8>) -> Result<Self, ExprError> {
+        let exponent = |a, b| f(a, b).ok_or_else(|| ExprError::Syntax("unit exponent out of range".to_owned()));
+        Ok(Self {
+            length: exponent(self.length, other.length)?,
+            time: exponent(self.time, other.time)?,
+            angle: exponent(self.angle, other.angle)?,
+            mass: exponent(self.mass, other.mass)?,
+        })
+    }
+}
+
+/// A value in SI units together with its dimension
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub dimension: Dimension,
+}
+
+impl Quantity {
+    pub fn new(value: f64, dimension: Dimension) -> Self {
+        Self { value, dimension }
+    }
+
+    pub fn scalar(value: f64) -> Self {
+        Self::new(value, Dimension::NONE)
+    }
+
+    /// The quantity as an expression in SI base units, e.g. `2.5 * m ^ 1 * s ^ -1`
+    fn to_base_units(self) -> String {
+        let units = [("m", self.dimension.length), ("s", self.dimension.time), ("rad", self.dimension.angle), ("kg", self.dimension.mass)];
+        let mut exp = self.value.to_string();
+        for (unit, exponent) in units.into_iter().filter(|&(_, exponent)| exponent != 0) {
+            exp += &format!(" * {} ^ {}", unit, exponent);
+        }
+        exp
+    }
+
+    /// The SI value, if the quantity has the expected dimension
+    pub fn expect(&self, dimension: Dimension) -> Result<f64, ExprError> {
+        if self.dimension == dimension {
+            Ok(self.value)
+        } else {
+            Err(ExprError::Dimension { expected: dimension, found: self.dimension })
+        }
+    }
+}
+
+/// Named expressions an `Expr` can reference, e.g. the variables of a Choreo project.
+/// Pose variables are stored as `name.x`, `name.y` and `name.heading`
+#[derive(Clone, Debug, Default)]
+pub struct Variables {
+    expressions: HashMap<String, Expr>,
+}
+
+impl Variables {
+    pub fn insert(&mut self, name: impl Into<String>, expr: Expr) {
+        self.expressions.insert(name.into(), expr);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Expr> {
+        self.expressions.get(name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprError {
+    Syntax(String),
+    UnknownName(String),
+    Dimension { expected: Dimension, found: Dimension },
+    /// A variable refers back to itself
+    Recursion(String),
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::Syntax(msg) => write!(f, "syntax error: {}", msg),
+            ExprError::UnknownName(name) => write!(f, "unknown unit or variable `{}`", name),
+            ExprError::Dimension { expected, found } => write!(f, "expected dimension {:?}, found {:?}", expected, found),
+            ExprError::Recursion(name) => write!(f, "variable `{}` is defined recursively", name),
+        }
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+/// Resolve every expression in a JSON document against `variables`: `exp` becomes the value in SI
+/// base units, so it can be evaluated and dimension-checked without the variables, and `val`
+/// the SI value. Expressions that can't be evaluated are left as they are
+pub(crate) fn resolve_values(value: &mut serde_json::Value, variables: &Variables) {
+    match value {
+        serde_json::Value::Object(object) => {
+            let resolved = object.get("exp")
+                .and_then(serde_json::Value::as_str)
+                .and_then(|exp| evaluate(exp, variables, 0).ok())
+                .filter(|resolved| resolved.value.is_finite());
+            if let Some(resolved) = resolved {
+                object.insert("exp".to_owned(), serde_json::json!(resolved.to_base_units()));
+                object.insert("val".to_owned(), serde_json::json!(resolved.value));
+            }
+            object.values_mut().for_each(|child| resolve_values(child, variables));
+        }
+        serde_json::Value::Array(array) => array.iter_mut().for_each(|child| resolve_values(child, variables)),
+        _ => {}
+    }
+}
+
+/// Variables referencing variables deeper than this are treated as recursive
+const MAX_DEPTH: usize = 32;
+
+fn evaluate(exp: &str, variables: &Variables, depth: usize) -> Result<Quantity, ExprError> {
+    let mut parser = Parser { chars: exp.chars().collect(), pos: 0, variables, depth };
+    let value = parser.expr()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(ExprError::Syntax(format!("unexpected `{}` in `{}`", parser.chars[parser.pos], exp)));
+    }
+    Ok(value)
+}
+
+fn unit(name: &str) -> Option<Quantity> {
+    let (factor, dimension) = match name {
+        "m" => (1., Dimension::LENGTH),
+        "cm" => (0.01, Dimension::LENGTH),
+        "mm" => (0.001, Dimension::LENGTH),
+        "km" => (1000., Dimension::LENGTH),
+        "in" | "inch" => (0.0254, Dimension::LENGTH),
+        "ft" | "feet" => (0.3048, Dimension::LENGTH),
+        "s" => (1., Dimension::TIME),
+        "ms" => (0.001, Dimension::TIME),
+        "min" => (60., Dimension::TIME),
+        "rad" => (1., Dimension::ANGLE),
+        "deg" => (PI / 180., Dimension::ANGLE),
+        "rot" | "rev" => (2. * PI, Dimension::ANGLE),
+        "rpm" | "RPM" => (2. * PI / 60., Dimension::ANGULAR_VELOCITY),
+        "kg" => (1., Dimension::MASS),
+        "g" => (0.001, Dimension::MASS),
+        "lb" | "lbs" => (0.45359237, Dimension::MASS),
+        "N" => (1., Dimension::FORCE),
+        "pi" => (PI, Dimension::NONE),
+        _ => return None,
+    };
+    Some(Quantity::new(factor, dimension))
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    variables: &'a Variables,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn expr(&mut self) -> Result<Quantity, ExprError> {
+        let mut lhs = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            if lhs.dimension != rhs.dimension {
+                return Err(ExprError::Dimension { expected: lhs.dimension, found: rhs.dimension });
+            }
+            lhs.value = if op == '+' { lhs.value + rhs.value } else { lhs.value - rhs.value };
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Quantity, ExprError> {
+        let mut lhs = self.unary()?;
+        loop {
+            match self.peek() {
+                Some('*') => {
+                    self.pos += 1;
+                    let rhs = self.unary()?;
+                    lhs = Quantity::new(lhs.value * rhs.value, lhs.dimension.mul(rhs.dimension)?);
+                }
+                Some('/') => {
+                    self.pos += 1;
+                    let rhs = self.unary()?;
+                    lhs = Quantity::new(lhs.value / rhs.value, lhs.dimension.mul(rhs.dimension.powi(-1)?)?);
+                }
+                _ => return Ok(lhs),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Quantity, ExprError> {
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                let value = self.unary()?;
+                Ok(Quantity::new(-value.value, value.dimension))
+            }
+            Some('+') => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.implicit(),
+        }
+    }
+
+    /// Implicit multiplication, as in `1.5 m` or `2 (x + 1 m)`. It binds tighter than `*` and `/`,
+    /// as in Choreo, so `3 m / 0.5 s` is a velocity
+    fn implicit(&mut self) -> Result<Quantity, ExprError> {
+        let mut lhs = self.power()?;
+        while self.peek().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '(') {
+            let rhs = self.power()?;
+            lhs = Quantity::new(lhs.value * rhs.value, lhs.dimension.mul(rhs.dimension)?);
+        }
+        Ok(lhs)
+    }
+
+    fn power(&mut self) -> Result<Quantity, ExprError> {
+        let base = self.primary()?;
+        if self.peek() != Some('^') {
+            return Ok(base);
+        }
+        self.pos += 1;
+        let exponent = self.exponent()?.expect(Dimension::NONE)?;
+        if base.dimension == Dimension::NONE {
+            return Ok(Quantity::scalar(base.value.powf(exponent)));
+        }
+        if exponent.fract() != 0. || exponent.abs() > i8::MAX as f64 {
+            return Err(ExprError::Syntax(format!("can't raise a unit to the power {}", exponent)));
+        }
+        Ok(Quantity::new(base.value.powi(exponent as i32), base.dimension.powi(exponent as i8)?))
+    }
+
+    /// A signed power, without implicit multiplication, so `m ^ -1 s` is `m ^ -1` times `s`
+    fn exponent(&mut self) -> Result<Quantity, ExprError> {
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                let value = self.exponent()?;
+                Ok(Quantity::new(-value.value, value.dimension))
+            }
+            Some('+') => {
+                self.pos += 1;
+                self.exponent()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Quantity, ExprError> {
+        match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                let value = self.expr()?;
+                if self.peek() != Some(')') {
+                    return Err(ExprError::Syntax("missing `)`".to_owned()));
+                }
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() || c == '_' => self.name(),
+            Some(c) => Err(ExprError::Syntax(format!("unexpected `{}`", c))),
+            None => Err(ExprError::Syntax("unexpected end of expression".to_owned())),
+        }
+    }
+
+    fn number(&mut self) -> Result<Quantity, ExprError> {
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit() || *c == '.') {
+            self.pos += 1;
+        }
+        // Exponent, but not the start of a unit or name like `2 em`
+        if matches!(self.chars.get(self.pos), Some('e' | 'E'))
+            && self.chars.get(self.pos + 1).is_some_and(|c| c.is_ascii_digit() || *c == '-' || *c == '+') {
+            self.pos += 2;
+            while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse()
+            .map(Quantity::scalar)
+            .map_err(|_| ExprError::Syntax(format!("invalid number `{}`", text)))
+    }
+
+    fn name(&mut self) -> Result<Quantity, ExprError> {
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_alphanumeric() || *c == '_' || *c == '.') {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+
+        if let Some(expr) = self.variables.get(&name) {
+            if self.depth >= MAX_DEPTH {
+                return Err(ExprError::Recursion(name));
+            }
+            return match evaluate(&expr.exp, self.variables, self.depth + 1) {
+                Err(ExprError::Recursion(_)) => Err(ExprError::Recursion(name)),
+                result => result,
+            };
+        }
+        unit(&name).ok_or(ExprError::UnknownName(name))
+    }
+}
+
+#[test]
+fn evaluate_units() {
+    let mut variables = Variables::default();
+    variables.insert("offset", Expr::new("10 cm"));
+    variables.insert("start.x", Expr::new("2 m + offset"));
+
+    let x = Expr::new("start.x * 2").length(&variables).unwrap();
+    assert!((x.get::<meter>() - 4.2).abs() < 1e-9);
+
+    let v = Expr::new("4 m / s").velocity(&variables).unwrap();
+    assert_eq!(v.get::<meter_per_second>(), 4.);
+
+    let heading = Expr::new("-90 deg").angle(&variables).unwrap();
+    assert!((heading.get::<radian>() + PI / 2.).abs() < 1e-9);
+
+    assert!(Expr::new("1 m + 1 s").evaluate(&variables).is_err());
+    assert!(Expr::new("1 m").angle(&variables).is_err());
+
+    let v = Expr::new("3 m / 0.5 s").velocity(&variables).unwrap();
+    assert_eq!(v.get::<meter_per_second>(), 6.);
+    assert_eq!(Expr::new("20 kg / 4 kg").evaluate(&variables), Ok(Quantity::scalar(5.)));
+    let v = Expr::new("1 ft / 2 ms").velocity(&variables).unwrap();
+    assert!((v.get::<meter_per_second>() - 152.4).abs() < 1e-9);
+    assert_eq!(Expr::new("6 kg m ^ 2").evaluate(&variables).unwrap().dimension, Dimension { length: 2, mass: 1, ..Dimension::NONE });
+    assert!(Expr::new("1 ft * 2 ms").velocity(&variables).is_err());
+
+    assert!(matches!(Expr::new("m^100 * m^100").evaluate(&variables), Err(ExprError::Syntax(_))));
+    assert!(matches!(Expr::new("1 / (m^-127 / m)").evaluate(&variables), Err(ExprError::Syntax(_))));
+
+    variables.insert("loop", Expr::new("loop + 1 m"));
+    assert_eq!(Expr::new("loop").evaluate(&variables), Err(ExprError::Recursion("loop".to_owned())));
+}
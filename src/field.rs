@@ -0,0 +1,162 @@
+use serde::{Serialize, Deserialize};
+use uom::si::f64::{Length, Time};
+use uom::si::{length::meter, time::second};
+use crate::{Path, Pose};
+
+/// Playing field dimensions and layout. Paths are always in blue-origin coordinates, with the
+/// blue alliance wall along x = 0
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(from = "RawField", into = "RawField")]
+pub struct Field {
+    /// Along x, between the alliance walls
+    pub length: Length,
+    /// Along y
+    pub width: Length,
+    pub symmetry: Symmetry,
+    /// Where poses from outside this crate, like vision measurements, put (0, 0)
+    pub origin: Origin,
+}
+
+/// How the red alliance's half of the field relates to the blue one
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Symmetry {
+    /// Rotated 180° about the center of the field, as in 2022 and 2025
+    #[default]
+    Rotational,
+    /// Reflected across the centerline x = length / 2, as in 2023 and 2024
+    Mirrored,
+}
+
+/// Position of the origin for coordinates converted with `Field::to_blue_origin`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Origin {
+    /// The corner on the blue alliance wall at y = 0, as WPILib and Choreo use
+    #[default]
+    BlueCorner,
+    /// The center of the field, as some vision systems report
+    Center,
+}
+
+/// Field layout in JSON, in meters
+#[derive(Serialize, Deserialize)]
+struct RawField {
+    length: f64,
+    width: f64,
+    #[serde(default)]
+    symmetry: Symmetry,
+    #[serde(default)]
+    origin: Origin,
+}
+
+impl From<RawField> for Field {
+    fn from(value: RawField) -> Self {
+        Field {
+            length: Length::new::<meter>(value.length),
+            width: Length::new::<meter>(value.width),
+            symmetry: value.symmetry,
+            origin: value.origin,
+        }
+    }
+}
+
+impl From<Field> for RawField {
+    fn from(value: Field) -> Self {
+        RawField {
+            length: value.length.get::<meter>(),
+            width: value.width.get::<meter>(),
+            symmetry: value.symmetry,
+            origin: value.origin,
+        }
+    }
+}
+
+impl Field {
+    /// A field with rotational symmetry and the origin in the blue corner
+    pub fn new(length: Length, width: Length) -> Self {
+        Field { length, width, symmetry: Symmetry::default(), origin: Origin::default() }
+    }
+
+    pub fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// The FRC field for `year`, from 2022 on, as laid out in Choreo
+    pub fn season(year: u32) -> Option<Self> {
+        let (length, width, symmetry) = match year {
+            2022 => (16.4592, 8.2296, Symmetry::Rotational),
+            2023 => (16.5418, 8.0137, Symmetry::Mirrored),
+            2024 => (16.5410, 8.2110, Symmetry::Mirrored),
+            2025 => (17.5480, 8.0520, Symmetry::Rotational),
+            _ => return None,
+        };
+        Some(Field::new(Length::new::<meter>(length), Length::new::<meter>(width)).with_symmetry(symmetry))
+    }
+
+    /// Load a field from JSON, with `length` and `width` in meters and optional `symmetry`
+    /// (`"rotational"` or `"mirrored"`) and `origin` (`"blueCorner"` or `"center"`)
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Whether the pose's position is on the field, edges included
+    pub fn contains(&self, pose: &Pose) -> bool {
+        let zero = Length::new::<meter>(0.);
+        zero <= pose.x && pose.x <= self.length && zero <= pose.y && pose.y <= self.width
+    }
+
+    /// Convert a pose measured from this field's origin to the blue-origin coordinates paths use
+    pub fn to_blue_origin(&self, pose: &Pose) -> Pose {
+        let (x, y) = self.origin_offset();
+        Pose { x: pose.x + x, y: pose.y + y, ..pose.clone() }
+    }
+
+    /// Convert a pose from blue-origin coordinates to this field's origin
+    pub fn to_field_origin(&self, pose: &Pose) -> Pose {
+        let (x, y) = self.origin_offset();
+        Pose { x: pose.x - x, y: pose.y - y, ..pose.clone() }
+    }
+
+    /// Position of this field's origin in blue-origin coordinates
+    fn origin_offset(&self) -> (Length, Length) {
+        match self.origin {
+            Origin::BlueCorner => (Length::new::<meter>(0.), Length::new::<meter>(0.)),
+            Origin::Center => (self.length / 2., self.width / 2.),
+        }
+    }
+}
+
+impl Path {
+    /// Time of the first sample whose position is off the field, if any
+    pub fn out_of_bounds(&self, field: &Field) -> Option<Time> {
+        self.samples.iter()
+            .find(|(_, pose)| !field.contains(pose))
+            .map(|(t, _)| Time::new::<second>(**t))
+    }
+}
+
+#[test]
+fn field_layout() {
+    let field = Field::from_json(r#"{"length": 16.0, "width": 8.0, "symmetry": "mirrored", "origin": "center"}"#).unwrap();
+    assert_eq!(field.symmetry, Symmetry::Mirrored);
+    assert_eq!(Field::season(2024).unwrap().symmetry, Symmetry::Mirrored);
+    assert!(Field::season(2019).is_none());
+
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let start = path.get(Time::new::<second>(0.));
+    let centered = field.to_field_origin(&start);
+    assert_eq!((centered.x.get::<meter>(), centered.y.get::<meter>()), (-7., -3.));
+    assert_eq!(field.to_blue_origin(&centered).x, start.x);
+
+    assert!(path.out_of_bounds(&field).is_none());
+    let small = Field::new(Length::new::<meter>(2.5), Length::new::<meter>(8.));
+    assert_eq!(path.out_of_bounds(&small), Some(Time::new::<second>(2.)));
+    assert_eq!(path.mirror(&field).get(Time::new::<second>(0.)).x.get::<meter>(), 15.);
+}
@@ -0,0 +1,412 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use uom::si::f64::{Angle, Length};
+use uom::si::{angle::radian, length::meter};
+use crate::Pose;
+
+/// Angles closer to zero than this use the series expansions in `exp` and `log`
+const EPSILON: f64 = 1e-9;
+
+/// A position, or a displacement between two positions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Translation2d {
+    pub x: Length,
+    pub y: Length,
+}
+
+/// A rotation, counterclockwise positive. Stored as its cosine and sine, so composing rotations
+/// never needs wrapping
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rotation2d {
+    cos: f64,
+    sin: f64,
+}
+
+/// A position and heading on the field
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pose2d {
+    pub translation: Translation2d,
+    pub rotation: Rotation2d,
+}
+
+/// A change of pose, expressed in the frame of the starting pose
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform2d {
+    pub translation: Translation2d,
+    pub rotation: Rotation2d,
+}
+
+/// A movement along a circular arc, in the frame of the starting pose. `dx` is forward
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Twist2d {
+    pub dx: Length,
+    pub dy: Length,
+    pub dtheta: Angle,
+}
+
+impl Translation2d {
+    pub fn new(x: Length, y: Length) -> Self {
+        Translation2d { x, y }
+    }
+
+    pub fn zero() -> Self {
+        Translation2d::new(Length::new::<meter>(0.), Length::new::<meter>(0.))
+    }
+
+    /// The point `distance` from the origin in the direction of `angle`
+    pub fn from_polar(distance: Length, angle: Rotation2d) -> Self {
+        Translation2d::new(distance * angle.cos, distance * angle.sin)
+    }
+
+    /// Distance from the origin
+    pub fn norm(&self) -> Length {
+        Length::new::<meter>(self.x.get::<meter>().hypot(self.y.get::<meter>()))
+    }
+
+    pub fn distance(&self, other: &Translation2d) -> Length {
+        (*other - *self).norm()
+    }
+
+    /// Direction from the origin. Zero for the origin itself
+    pub fn angle(&self) -> Rotation2d {
+        Rotation2d::from_components(self.x.get::<meter>(), self.y.get::<meter>())
+    }
+
+    /// Rotate counterclockwise about the origin
+    pub fn rotate_by(&self, rotation: Rotation2d) -> Self {
+        let (x, y) = rotation.rotate_vector(self.x, self.y);
+        Translation2d::new(x, y)
+    }
+
+    /// Linear interpolation, with `t` clamped to [0, 1]
+    pub fn interpolate(&self, end: &Translation2d, t: f64) -> Self {
+        *self + (*end - *self) * t.clamp(0., 1.)
+    }
+}
+
+impl Rotation2d {
+    pub fn new(angle: Angle) -> Self {
+        let angle = angle.get::<radian>();
+        Rotation2d { cos: angle.cos(), sin: angle.sin() }
+    }
+
+    pub fn identity() -> Self {
+        Rotation2d { cos: 1., sin: 0. }
+    }
+
+    /// The direction of the vector (x, y), which doesn't need to be normalized. Zero if it has
+    /// no length
+    pub fn from_components(x: f64, y: f64) -> Self {
+        let norm = x.hypot(y);
+        if norm > EPSILON {
+            Rotation2d { cos: x / norm, sin: y / norm }
+        } else {
+            Rotation2d::identity()
+        }
+    }
+
+    /// The angle, in (-π, π]
+    pub fn angle(&self) -> Angle {
+        Angle::new::<radian>(self.sin.atan2(self.cos))
+    }
+
+    pub fn cos(&self) -> f64 {
+        self.cos
+    }
+
+    pub fn sin(&self) -> f64 {
+        self.sin
+    }
+
+    pub fn rotate_by(&self, other: Rotation2d) -> Self {
+        Rotation2d {
+            cos: self.cos * other.cos - self.sin * other.sin,
+            sin: self.cos * other.sin + self.sin * other.cos,
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Rotation2d { cos: self.cos, sin: -self.sin }
+    }
+
+    /// Rotate the vector (x, y) counterclockwise. Works for any quantity, like a velocity
+    pub fn rotate_vector<Q>(&self, x: Q, y: Q) -> (Q, Q)
+    where Q: Add<Output = Q> + Sub<Output = Q> + Mul<f64, Output = Q> + Copy {
+        (x * self.cos - y * self.sin, x * self.sin + y * self.cos)
+    }
+
+    /// Interpolation the short way around, with `t` clamped to [0, 1]
+    pub fn interpolate(&self, end: &Rotation2d, t: f64) -> Self {
+        *self + Rotation2d::new((*end - *self).angle() * t.clamp(0., 1.))
+    }
+}
+
+impl Pose2d {
+    pub fn new(translation: Translation2d, rotation: Rotation2d) -> Self {
+        Pose2d { translation, rotation }
+    }
+
+    pub fn identity() -> Self {
+        Pose2d::new(Translation2d::zero(), Rotation2d::identity())
+    }
+
+    pub fn x(&self) -> Length {
+        self.translation.x
+    }
+
+    pub fn y(&self) -> Length {
+        self.translation.y
+    }
+
+    /// Apply `transform` in this pose's frame
+    pub fn transform_by(&self, transform: &Transform2d) -> Self {
+        Pose2d::new(
+            self.translation + transform.translation.rotate_by(self.rotation),
+            self.rotation + transform.rotation,
+        )
+    }
+
+    /// This pose as seen from `other`, so `other.transform_by` of the result gives it back
+    pub fn relative_to(&self, other: &Pose2d) -> Self {
+        let transform = Transform2d::between(other, self);
+        Pose2d::new(transform.translation, transform.rotation)
+    }
+
+    /// Rotate about the origin, turning the heading with it
+    pub fn rotate_by(&self, rotation: Rotation2d) -> Self {
+        Pose2d::new(self.translation.rotate_by(rotation), self.rotation + rotation)
+    }
+
+    /// The pose after following `twist` from this one
+    pub fn exp(&self, twist: &Twist2d) -> Self {
+        let (dx, dy, dtheta) = (twist.dx, twist.dy, twist.dtheta.get::<radian>());
+        let (sin, cos) = dtheta.sin_cos();
+        let (sin_term, cos_term) = if dtheta.abs() < EPSILON {
+            (1. - dtheta * dtheta / 6., dtheta / 2.)
+        } else {
+            (sin / dtheta, (1. - cos) / dtheta)
+        };
+        self.transform_by(&Transform2d::new(
+            Translation2d::new(dx * sin_term - dy * cos_term, dx * cos_term + dy * sin_term),
+            Rotation2d { cos, sin },
+        ))
+    }
+
+    /// The twist that takes this pose to `end`, the inverse of `exp`
+    pub fn log(&self, end: &Pose2d) -> Twist2d {
+        let transform = end.relative_to(self);
+        let dtheta = transform.rotation.angle().get::<radian>();
+        let half_dtheta = dtheta / 2.;
+        let cos_minus_one = transform.rotation.cos - 1.;
+        let half_theta_by_tan = if cos_minus_one.abs() < EPSILON {
+            1. - dtheta * dtheta / 12.
+        } else {
+            -(half_dtheta * transform.rotation.sin) / cos_minus_one
+        };
+        let translation = transform.translation
+            .rotate_by(Rotation2d::from_components(half_theta_by_tan, -half_dtheta))
+            * half_theta_by_tan.hypot(half_dtheta);
+        Twist2d { dx: translation.x, dy: translation.y, dtheta: Angle::new::<radian>(dtheta) }
+    }
+
+    /// Interpolation along the constant-curvature arc between the poses, with `t` clamped to [0, 1]
+    pub fn interpolate(&self, end: &Pose2d, t: f64) -> Self {
+        match t {
+            t if t <= 0. => *self,
+            t if t >= 1. => *end,
+            t => self.exp(&(self.log(end) * t)),
+        }
+    }
+}
+
+impl Transform2d {
+    pub fn new(translation: Translation2d, rotation: Rotation2d) -> Self {
+        Transform2d { translation, rotation }
+    }
+
+    pub fn identity() -> Self {
+        Transform2d::new(Translation2d::zero(), Rotation2d::identity())
+    }
+
+    /// The transform that takes `initial` to `last`
+    pub fn between(initial: &Pose2d, last: &Pose2d) -> Self {
+        Transform2d::new(
+            (last.translation - initial.translation).rotate_by(initial.rotation.inverse()),
+            last.rotation - initial.rotation,
+        )
+    }
+
+    pub fn inverse(&self) -> Self {
+        Transform2d::new((-self.translation).rotate_by(self.rotation.inverse()), self.rotation.inverse())
+    }
+}
+
+impl Twist2d {
+    pub fn new(dx: Length, dy: Length, dtheta: Angle) -> Self {
+        Twist2d { dx, dy, dtheta }
+    }
+}
+
+impl Pose {
+    /// Position and heading, without the velocities
+    pub fn pose2d(&self) -> Pose2d {
+        Pose2d::new(self.translation(), self.rotation())
+    }
+
+    pub fn translation(&self) -> Translation2d {
+        Translation2d::new(self.x, self.y)
+    }
+
+    pub fn rotation(&self) -> Rotation2d {
+        Rotation2d::new(self.heading)
+    }
+}
+
+impl From<&Pose> for Pose2d {
+    fn from(value: &Pose) -> Self {
+        value.pose2d()
+    }
+}
+
+impl Add for Translation2d {
+    type Output = Translation2d;
+
+    fn add(self, rhs: Translation2d) -> Self::Output {
+        Translation2d::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Translation2d {
+    type Output = Translation2d;
+
+    fn sub(self, rhs: Translation2d) -> Self::Output {
+        Translation2d::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for Translation2d {
+    type Output = Translation2d;
+
+    fn neg(self) -> Self::Output {
+        Translation2d::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<f64> for Translation2d {
+    type Output = Translation2d;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Translation2d::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Div<f64> for Translation2d {
+    type Output = Translation2d;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Translation2d::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl Add for Rotation2d {
+    type Output = Rotation2d;
+
+    fn add(self, rhs: Rotation2d) -> Self::Output {
+        self.rotate_by(rhs)
+    }
+}
+
+impl Sub for Rotation2d {
+    type Output = Rotation2d;
+
+    fn sub(self, rhs: Rotation2d) -> Self::Output {
+        self.rotate_by(rhs.inverse())
+    }
+}
+
+impl Neg for Rotation2d {
+    type Output = Rotation2d;
+
+    fn neg(self) -> Self::Output {
+        self.inverse()
+    }
+}
+
+impl Add<Transform2d> for Pose2d {
+    type Output = Pose2d;
+
+    fn add(self, rhs: Transform2d) -> Self::Output {
+        self.transform_by(&rhs)
+    }
+}
+
+impl Sub for Pose2d {
+    type Output = Transform2d;
+
+    fn sub(self, rhs: Pose2d) -> Self::Output {
+        Transform2d::between(&rhs, &self)
+    }
+}
+
+/// Apply `self` and then `rhs`, in the frame left by `self`
+impl Add for Transform2d {
+    type Output = Transform2d;
+
+    fn add(self, rhs: Transform2d) -> Self::Output {
+        Transform2d::between(&Pose2d::identity(), &(Pose2d::identity() + self + rhs))
+    }
+}
+
+impl Mul<f64> for Twist2d {
+    type Output = Twist2d;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Twist2d::new(self.dx * rhs, self.dy * rhs, self.dtheta * rhs)
+    }
+}
+
+#[cfg(test)]
+fn assert_close(a: &Pose2d, b: &Pose2d) {
+    assert!(a.translation.distance(&b.translation).get::<meter>() < 1e-9, "{a:?} != {b:?}");
+    assert!((a.rotation - b.rotation).angle().get::<radian>().abs() < 1e-9, "{a:?} != {b:?}");
+}
+
+#[test]
+fn composition() {
+    let m = Length::new::<meter>;
+    let deg = |degrees: f64| Rotation2d::new(Angle::new::<uom::si::angle::degree>(degrees));
+
+    let start = Pose2d::new(Translation2d::new(m(1.), m(2.)), deg(90.));
+    let transform = Transform2d::new(Translation2d::new(m(1.), m(0.)), deg(45.));
+    let end = start + transform;
+    assert_close(&end, &Pose2d::new(Translation2d::new(m(1.), m(3.)), deg(135.)));
+    assert_close(&(end + transform.inverse()), &start);
+    assert_eq!(end - start, Transform2d::between(&start, &end));
+    assert_close(&end.relative_to(&start), &Pose2d::new(transform.translation, transform.rotation));
+    assert_close(&start.rotate_by(deg(-90.)), &Pose2d::new(Translation2d::new(m(2.), m(-1.)), deg(0.)));
+
+    let twice = Pose2d::identity() + (transform + transform);
+    assert_close(&twice, &(Pose2d::identity() + transform + transform));
+}
+
+#[test]
+fn exp_log() {
+    let m = Length::new::<meter>;
+    let start = Pose2d::new(Translation2d::new(m(1.), m(2.)), Rotation2d::new(Angle::new::<radian>(0.3)));
+
+    // A quarter circle of radius 1 to the left
+    let arc = Twist2d::new(m(std::f64::consts::FRAC_PI_2), m(0.), Angle::new::<radian>(std::f64::consts::FRAC_PI_2));
+    let end = Pose2d::identity().exp(&arc);
+    assert_close(&end, &Pose2d::new(Translation2d::new(m(1.), m(1.)), Rotation2d::new(arc.dtheta)));
+
+    let end = start.exp(&arc);
+    let twist = start.log(&end);
+    assert!((twist.dx - arc.dx).get::<meter>().abs() < 1e-9);
+    assert!(twist.dy.get::<meter>().abs() < 1e-9);
+    assert!((twist.dtheta - arc.dtheta).get::<radian>().abs() < 1e-9);
+
+    // Straight-line twists take the series expansion
+    let straight = Twist2d::new(m(2.), m(1.), Angle::new::<radian>(0.));
+    let twist = start.log(&start.exp(&straight));
+    assert!((twist.dx - straight.dx).get::<meter>().abs() < 1e-9 && (twist.dy - straight.dy).get::<meter>().abs() < 1e-9);
+    assert_close(&start.interpolate(&end, 0.5), &start.exp(&(arc * 0.5)));
+}
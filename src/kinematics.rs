@@ -0,0 +1,208 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+use uom::si::f64::{Angle, AngularVelocity, Velocity};
+use uom::si::{angle::radian, angular_velocity::radian_per_second, length::meter, velocity::meter_per_second};
+use crate::geometry::{Rotation2d, Translation2d};
+use crate::{Params, Pose};
+
+/// Robot-relative velocity, with x forward and y to the left
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChassisSpeeds {
+    pub vx: Velocity,
+    pub vy: Velocity,
+    pub omega: AngularVelocity,
+}
+
+/// Speed and direction of one swerve module's wheel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SwerveModuleState {
+    pub speed: Velocity,
+    pub angle: Rotation2d,
+}
+
+/// Converts between chassis speeds and module states for a swerve drive
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwerveKinematics {
+    /// Position of every module relative to the robot's center
+    modules: Vec<Translation2d>,
+}
+
+impl ChassisSpeeds {
+    pub fn new(vx: Velocity, vy: Velocity, omega: AngularVelocity) -> Self {
+        ChassisSpeeds { vx, vy, omega }
+    }
+
+    /// Robot-relative speeds for a field-relative velocity, with the robot facing `heading`
+    pub fn from_field_relative(vx: Velocity, vy: Velocity, omega: AngularVelocity, heading: Rotation2d) -> Self {
+        let (vx, vy) = heading.inverse().rotate_vector(vx, vy);
+        ChassisSpeeds::new(vx, vy, omega)
+    }
+}
+
+impl Pose {
+    /// The pose's field-relative velocity as robot-relative chassis speeds
+    pub fn chassis_speeds(&self) -> ChassisSpeeds {
+        ChassisSpeeds::from_field_relative(self.velocity_x, self.velocity_y, self.angular_velocity, self.rotation())
+    }
+}
+
+impl SwerveModuleState {
+    pub fn new(speed: Velocity, angle: Rotation2d) -> Self {
+        SwerveModuleState { speed, angle }
+    }
+
+    /// The equivalent state that turns the module at most 90° from `current`, reversing the wheel
+    /// if needed. A stopped module keeps its current angle instead of turning
+    pub fn optimize(&self, current: Rotation2d) -> Self {
+        if self.speed.get::<meter_per_second>() == 0. {
+            return SwerveModuleState::new(self.speed, current);
+        }
+        if (self.angle - current).angle().get::<radian>().abs() > FRAC_PI_2 {
+            SwerveModuleState::new(-self.speed, self.angle + Rotation2d::new(Angle::new::<radian>(PI)))
+        } else {
+            *self
+        }
+    }
+}
+
+impl SwerveKinematics {
+    /// `modules` are relative to the robot's center, x forward and y to the left. Module states are
+    /// in the same order
+    pub fn new(modules: Vec<Translation2d>) -> Self {
+        SwerveKinematics { modules }
+    }
+
+    /// Kinematics for the modules of a Choreo robot config, in front left, front right, back
+    /// left, back right order
+    pub fn from_params(params: &Params) -> Self {
+        SwerveKinematics::new(params.modules.clone())
+    }
+
+    pub fn modules(&self) -> &[Translation2d] {
+        &self.modules
+    }
+
+    /// The state of every module for the robot to move at `speeds`. Stopped modules point
+    /// forward, so pass the result through `SwerveModuleState::optimize` to hold their angle
+    pub fn to_module_states(&self, speeds: &ChassisSpeeds) -> Vec<SwerveModuleState> {
+        let (vx, vy) = (speeds.vx.get::<meter_per_second>(), speeds.vy.get::<meter_per_second>());
+        let omega = speeds.omega.get::<radian_per_second>();
+        self.modules.iter().map(|module| {
+            let (x, y) = (module.x.get::<meter>(), module.y.get::<meter>());
+            let (module_vx, module_vy) = (vx - omega * y, vy + omega * x);
+            SwerveModuleState::new(
+                Velocity::new::<meter_per_second>(module_vx.hypot(module_vy)),
+                Rotation2d::from_components(module_vx, module_vy),
+            )
+        }).collect()
+    }
+
+    /// Module states for a sampled pose, from its robot-relative chassis speeds
+    pub fn pose_to_module_states(&self, pose: &Pose) -> Vec<SwerveModuleState> {
+        self.to_module_states(&pose.chassis_speeds())
+    }
+
+    /// The chassis speeds that best match `states`, in the least-squares sense when the modules
+    /// disagree. Extra or missing states are ignored
+    pub fn to_chassis_speeds(&self, states: &[SwerveModuleState]) -> ChassisSpeeds {
+        // Normal equations for module velocity = (vx - ω y, vy + ω x)
+        let mut normal = [[0.; 3]; 3];
+        let mut rhs = [0.; 3];
+        for (module, state) in self.modules.iter().zip(states) {
+            let (x, y) = (module.x.get::<meter>(), module.y.get::<meter>());
+            let speed = state.speed.get::<meter_per_second>();
+            let (module_vx, module_vy) = (speed * state.angle.cos(), speed * state.angle.sin());
+            for (coefficients, value) in [([1., 0., -y], module_vx), ([0., 1., x], module_vy)] {
+                for i in 0..3 {
+                    for j in 0..3 {
+                        normal[i][j] += coefficients[i] * coefficients[j];
+                    }
+                    rhs[i] += coefficients[i] * value;
+                }
+            }
+        }
+
+        let [vx, vy, omega] = solve(normal, rhs).unwrap_or([0.; 3]);
+        ChassisSpeeds::new(
+            Velocity::new::<meter_per_second>(vx),
+            Velocity::new::<meter_per_second>(vy),
+            AngularVelocity::new::<radian_per_second>(omega),
+        )
+    }
+
+    /// Scale every module down by the same factor so none exceeds `max_speed`, keeping the
+    /// direction of motion. A robot config's limit is `Params::max_wheel_speed`
+    pub fn desaturate(states: &mut [SwerveModuleState], max_speed: Velocity) {
+        let max_speed = max_speed.get::<meter_per_second>().abs();
+        let fastest = states.iter().map(|state| state.speed.get::<meter_per_second>().abs()).fold(0., f64::max);
+        if fastest > max_speed {
+            for state in states {
+                state.speed *= max_speed / fastest;
+            }
+        }
+    }
+}
+
+/// Solve the 3x3 system `a x = b` by Cramer's rule. None if it is singular, like with fewer than
+/// two distinct modules
+fn solve(a: [[f64; 3]; 3], b: [f64; 3]) -> Option<[f64; 3]> {
+    let determinant = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let det = determinant(a);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let mut x = [0.; 3];
+    for (column, x) in x.iter_mut().enumerate() {
+        let mut m = a;
+        for row in 0..3 {
+            m[row][column] = b[row];
+        }
+        *x = determinant(m) / det;
+    }
+    Some(x)
+}
+
+#[test]
+fn swerve_kinematics() {
+    use uom::si::f64::Length;
+
+    let m = Length::new::<meter>;
+    let mps = Velocity::new::<meter_per_second>;
+    let kinematics = SwerveKinematics::new(vec![
+        Translation2d::new(m(0.3), m(0.3)),
+        Translation2d::new(m(0.3), m(-0.3)),
+        Translation2d::new(m(-0.3), m(0.3)),
+        Translation2d::new(m(-0.3), m(-0.3)),
+    ]);
+
+    let spin = ChassisSpeeds::new(mps(0.), mps(0.), AngularVelocity::new::<radian_per_second>(1.));
+    let states = kinematics.to_module_states(&spin);
+    assert!((states[0].speed.get::<meter_per_second>() - 0.3 * 2_f64.sqrt()).abs() < 1e-9);
+    assert!((states[0].angle.angle().get::<radian>() - 3. * std::f64::consts::FRAC_PI_4).abs() < 1e-9);
+
+    let speeds = ChassisSpeeds::new(mps(1.), mps(-0.5), AngularVelocity::new::<radian_per_second>(2.));
+    let round_trip = kinematics.to_chassis_speeds(&kinematics.to_module_states(&speeds));
+    assert!((round_trip.vx - speeds.vx).get::<meter_per_second>().abs() < 1e-9);
+    assert!((round_trip.vy - speeds.vy).get::<meter_per_second>().abs() < 1e-9);
+    assert!((round_trip.omega - speeds.omega).get::<radian_per_second>().abs() < 1e-9);
+
+    let mut states = kinematics.to_module_states(&speeds);
+    SwerveKinematics::desaturate(&mut states, mps(1.));
+    let fastest = states.iter().map(|state| state.speed.get::<meter_per_second>()).fold(0., f64::max);
+    assert!((fastest - 1.).abs() < 1e-9);
+
+    let backwards = SwerveModuleState::new(mps(1.), Rotation2d::new(Angle::new::<radian>(PI)));
+    let optimized = backwards.optimize(Rotation2d::new(Angle::new::<radian>(0.1)));
+    assert_eq!(optimized.speed, mps(-1.));
+    assert!(optimized.angle.angle().get::<radian>().abs() < 1e-9);
+
+    let path = crate::Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let mut pose = path.get(uom::si::f64::Time::new::<uom::si::time::second>(1.));
+    pose.heading = Angle::new::<radian>(FRAC_PI_2);
+    let chassis = pose.chassis_speeds();
+    assert!(chassis.vx.get::<meter_per_second>().abs() < 1e-9);
+    assert!((chassis.vy.get::<meter_per_second>() + 1.).abs() < 1e-9);
+}
@@ -1,24 +1,168 @@
-#![feature(btree_cursors)]
-
-use std::{collections::BTreeMap, process::Output, ops::{Mul, Add, Sub, Deref}};
+use std::{collections::BTreeMap, ops::{Bound, Mul, Add, Sub}};
 use std::f64::consts::PI;
 use ordered_float::NotNan;
 use serde::{Serialize, Deserialize};
 use uom::si::{f64::{AngularVelocity, Length, Angle, Velocity, Time},
-              length::meter, angle::{radian, degree},
+              length::meter, angle::radian,
               velocity::meter_per_second, angular_velocity::radian_per_second, time::second};
-use uom::si::length::millimeter;
+use uom::si::f64::{Acceleration, AngularAcceleration, Jerk, Mass, MomentOfInertia, Torque};
+use uom::si::{acceleration::meter_per_second_squared, angular_acceleration::radian_per_second_squared,
+              jerk::meter_per_second_cubed};
+
+pub mod distance;
+pub mod error;
+pub mod export;
+pub mod expr;
+pub mod field;
+pub mod geometry;
+pub mod kinematics;
+pub mod pathplanner;
+pub mod project;
+pub mod resample;
+pub mod tracking;
+pub mod transform;
+pub mod wpilib;
+
+pub use error::TrajectoryError;
+pub use expr::{Expr, ExprError, Variables};
+use expr::Dimension;
+pub use field::{Field, Origin, Symmetry};
+pub use geometry::{Pose2d, Rotation2d, Transform2d, Translation2d, Twist2d};
+pub use kinematics::{ChassisSpeeds, SwerveKinematics, SwerveModuleState};
+pub use pathplanner::{PathPlannerAuto, PathPlannerError, PathPlannerPath};
+pub use project::{ChoreoProject, ProjectError};
+pub use tracking::{ClosestPoint, TrackingError};
 
 #[derive(Serialize, Deserialize)]
 pub struct ChoreoTrajectory {
     pub name: String,
     pub version: u32,
     pub snapshot: Snapshot,
-    pub params: Params,
+    pub params: PathParams,
     pub trajectory: TrajectoryData,
     pub events: Vec<Event>,
 }
 
+/// Choreo 2025 .traj layout. `params` holds the editor's unit expressions, `snapshot` the
+/// plain values the trajectory was last generated from
+#[derive(Serialize, Deserialize)]
+pub struct ChoreoTrajectory2025 {
+    pub name: String,
+    pub version: String,
+    pub snapshot: Snapshot,
+    pub params: PathParams,
+    pub trajectory: TrajectoryData2025,
+    pub events: Vec<Event>,
+}
+
+/// A .traj file in any of the supported schemas
+pub enum ChoreoFile {
+    V2024(ChoreoTrajectory),
+    V2025(ChoreoTrajectory2025),
+}
+
+impl ChoreoFile {
+    /// Detect the schema from the `version` field: 2024 files use a number, 2025 files a string
+    pub fn parse(data: &str) -> Result<Self, serde_json::Error> {
+        Self::from_value(serde_json::from_str(data)?)
+    }
+
+    pub fn from_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        if value.get("version").is_some_and(|version| version.is_string()) {
+            Ok(ChoreoFile::V2025(serde_json::from_value(value)?))
+        } else {
+            Ok(ChoreoFile::V2024(serde_json::from_value(value)?))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ValueOrExpr {
+    Value(f64),
+    Expr(Expr),
+}
+
+/// Accept either a plain number or an expression of `dimension`, so 2024 and 2025 files share
+/// types. A bare number is taken to be in SI units
+fn value_or_expr<'de, D: serde::Deserializer<'de>>(deserializer: D, dimension: Dimension) -> Result<f64, D::Error> {
+    Ok(match ValueOrExpr::deserialize(deserializer)? {
+        ValueOrExpr::Value(value) => value,
+        ValueOrExpr::Expr(expr) => expr.evaluate_as(&Variables::default(), dimension).map_err(serde::de::Error::custom)?,
+    })
+}
+
+fn length_value<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    value_or_expr(deserializer, Dimension::LENGTH)
+}
+
+fn angle_value<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    value_or_expr(deserializer, Dimension::ANGLE)
+}
+
+fn time_value<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    value_or_expr(deserializer, Dimension::TIME)
+}
+
+fn velocity_value<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    value_or_expr(deserializer, Dimension::VELOCITY)
+}
+
+fn angular_velocity_value<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    value_or_expr(deserializer, Dimension::ANGULAR_VELOCITY)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PathParams {
+    pub waypoints: Vec<ParamsWaypoint>,
+    pub constraints: Vec<Constraint>,
+    #[serde(rename = "targetDt")]
+    pub target_dt: Expr,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ParamsWaypoint {
+    pub x: Expr,
+    pub y: Expr,
+    pub heading: Expr,
+    pub intervals: u32,
+    pub split: bool,
+    #[serde(rename = "fixTranslation")]
+    pub fix_translation: bool,
+    #[serde(rename = "fixHeading")]
+    pub fix_heading: bool,
+    #[serde(rename = "overrideIntervals")]
+    pub override_intervals: bool,
+}
+
+impl ParamsWaypoint {
+    /// Waypoint pose with its expressions evaluated against `variables`. Velocities are zero
+    pub fn pose(&self, variables: &Variables) -> Result<Pose, ExprError> {
+        Ok(Pose {
+            x: self.x.length(variables)?,
+            y: self.y.length(variables)?,
+            heading: self.heading.angle(variables)?,
+            angular_velocity: AngularVelocity::new::<radian_per_second>(0.),
+            velocity_x: Velocity::new::<meter_per_second>(0.),
+            velocity_y: Velocity::new::<meter_per_second>(0.),
+            angular_acceleration: AngularAcceleration::new::<radian_per_second_squared>(0.),
+            acceleration_x: Acceleration::new::<meter_per_second_squared>(0.),
+            acceleration_y: Acceleration::new::<meter_per_second_squared>(0.),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TrajectoryData2025 {
+    /// `"Swerve"` or `"DifferentialDrive"`, which decides the layout of every sample
+    #[serde(rename = "sampleType")]
+    pub sample_type: Option<String>,
+    pub waypoints: Vec<f64>,
+    pub samples: Vec<ChoreoSample>,
+    /// Index of the first sample of every split
+    pub splits: Vec<usize>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Snapshot {
     pub waypoints: Vec<SnapshotWaypoint>,
@@ -42,19 +186,390 @@ pub struct SnapshotWaypoint {
     pub override_intervals: bool,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct Constraint {
-    // TODO: implement
+/// A waypoint reference as Choreo stores it: `"first"`, `"last"` or an index.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(from = "RawWaypointId", into = "RawWaypointId")]
+pub enum WaypointId {
+    First,
+    Last,
+    Index(usize),
 }
 
-#[derive(Serialize, Deserialize)]
+impl WaypointId {
+    /// Resolve to a concrete index into a list of `count` waypoints
+    pub fn resolve(&self, count: usize) -> Option<usize> {
+        match *self {
+            WaypointId::First if count > 0 => Some(0),
+            WaypointId::Last if count > 0 => Some(count - 1),
+            WaypointId::Index(i) if i < count => Some(i),
+            _ => None,
+        }
+    }
+
+    /// The same waypoint counted from the other end of a list of `count` waypoints
+    pub fn reversed(&self, count: usize) -> WaypointId {
+        match *self {
+            WaypointId::First => WaypointId::Last,
+            WaypointId::Last => WaypointId::First,
+            WaypointId::Index(i) if i < count => WaypointId::Index(count - 1 - i),
+            other => other,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+enum RawWaypointId {
+    Index(usize),
+    Named(WaypointAnchor),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "lowercase")]
+enum WaypointAnchor {
+    First,
+    Last,
+}
+
+impl From<RawWaypointId> for WaypointId {
+    fn from(value: RawWaypointId) -> Self {
+        match value {
+            RawWaypointId::Index(i) => WaypointId::Index(i),
+            RawWaypointId::Named(WaypointAnchor::First) => WaypointId::First,
+            RawWaypointId::Named(WaypointAnchor::Last) => WaypointId::Last,
+        }
+    }
+}
+
+impl From<WaypointId> for RawWaypointId {
+    fn from(value: WaypointId) -> Self {
+        match value {
+            WaypointId::First => RawWaypointId::Named(WaypointAnchor::First),
+            WaypointId::Last => RawWaypointId::Named(WaypointAnchor::Last),
+            WaypointId::Index(i) => RawWaypointId::Index(i),
+        }
+    }
+}
+
+/// Where a constraint applies: a single waypoint or the segment between two
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintScope {
+    Waypoint(WaypointId),
+    Segment(WaypointId, WaypointId),
+}
+
+impl ConstraintScope {
+    /// Start and end time of the scope, given the timestamp of every waypoint
+    pub fn time_range(&self, waypoint_times: &[f64]) -> Option<(f64, f64)> {
+        let count = waypoint_times.len();
+        match self {
+            ConstraintScope::Waypoint(wp) => {
+                let t = waypoint_times[wp.resolve(count)?];
+                Some((t, t))
+            }
+            ConstraintScope::Segment(from, to) => {
+                let from = waypoint_times[from.resolve(count)?];
+                let to = waypoint_times[to.resolve(count)?];
+                Some((from.min(to), from.max(to)))
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "RawConstraint", into = "RawConstraint")]
+pub enum Constraint {
+    StopPoint { scope: ConstraintScope, enabled: bool },
+    WptVelocityDirection { scope: ConstraintScope, enabled: bool, direction: Angle },
+    WptZeroVelocity { scope: ConstraintScope, enabled: bool },
+    MaxVelocity { scope: ConstraintScope, enabled: bool, max: Velocity },
+    MaxAngularVelocity { scope: ConstraintScope, enabled: bool, max: AngularVelocity },
+    ZeroAngularVelocity { scope: ConstraintScope, enabled: bool },
+    StraightLine { scope: ConstraintScope, enabled: bool },
+    PointAt { scope: ConstraintScope, enabled: bool, x: Length, y: Length, tolerance: Angle, flip: bool },
+    KeepInRectangle { scope: ConstraintScope, enabled: bool, x: Length, y: Length, w: Length, h: Length },
+    KeepInCircle { scope: ConstraintScope, enabled: bool, x: Length, y: Length, r: Length },
+    KeepOutCircle { scope: ConstraintScope, enabled: bool, x: Length, y: Length, r: Length },
+    /// A constraint type this crate doesn't model, kept as written so the file still loads and
+    /// saves unchanged
+    Other { scope: ConstraintScope, enabled: bool, kind: String, props: serde_json::Value },
+}
+
+impl Constraint {
+    pub fn scope(&self) -> ConstraintScope {
+        match self {
+            Constraint::StopPoint { scope, .. }
+            | Constraint::WptVelocityDirection { scope, .. }
+            | Constraint::WptZeroVelocity { scope, .. }
+            | Constraint::MaxVelocity { scope, .. }
+            | Constraint::MaxAngularVelocity { scope, .. }
+            | Constraint::ZeroAngularVelocity { scope, .. }
+            | Constraint::StraightLine { scope, .. }
+            | Constraint::PointAt { scope, .. }
+            | Constraint::KeepInRectangle { scope, .. }
+            | Constraint::KeepInCircle { scope, .. }
+            | Constraint::KeepOutCircle { scope, .. }
+            | Constraint::Other { scope, .. } => *scope,
+        }
+    }
+
+    fn scope_mut(&mut self) -> &mut ConstraintScope {
+        match self {
+            Constraint::StopPoint { scope, .. }
+            | Constraint::WptVelocityDirection { scope, .. }
+            | Constraint::WptZeroVelocity { scope, .. }
+            | Constraint::MaxVelocity { scope, .. }
+            | Constraint::MaxAngularVelocity { scope, .. }
+            | Constraint::ZeroAngularVelocity { scope, .. }
+            | Constraint::StraightLine { scope, .. }
+            | Constraint::PointAt { scope, .. }
+            | Constraint::KeepInRectangle { scope, .. }
+            | Constraint::KeepInCircle { scope, .. }
+            | Constraint::KeepOutCircle { scope, .. }
+            | Constraint::Other { scope, .. } => scope,
+        }
+    }
+
+    /// Whether the constraint is switched on in Choreo. Disabled constraints are kept so the
+    /// file round-trips, but don't apply
+    pub fn enabled(&self) -> bool {
+        match self {
+            Constraint::StopPoint { enabled, .. }
+            | Constraint::WptVelocityDirection { enabled, .. }
+            | Constraint::WptZeroVelocity { enabled, .. }
+            | Constraint::MaxVelocity { enabled, .. }
+            | Constraint::MaxAngularVelocity { enabled, .. }
+            | Constraint::ZeroAngularVelocity { enabled, .. }
+            | Constraint::StraightLine { enabled, .. }
+            | Constraint::PointAt { enabled, .. }
+            | Constraint::KeepInRectangle { enabled, .. }
+            | Constraint::KeepInCircle { enabled, .. }
+            | Constraint::KeepOutCircle { enabled, .. }
+            | Constraint::Other { enabled, .. } => *enabled,
+        }
+    }
+}
+
+/// Constraint layout in the .traj file, with plain SI values
+#[derive(Serialize, Deserialize, Clone)]
+struct RawConstraint {
+    from: WaypointId,
+    to: Option<WaypointId>,
+    data: RawConstraintKind,
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Constraint data of a modelled type, or of any other type as plain JSON
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+enum RawConstraintKind {
+    Known(RawConstraintData),
+    Other {
+        #[serde(rename = "type")]
+        kind: String,
+        #[serde(default)]
+        props: serde_json::Value,
+    },
+}
+
+/// Types in `RawConstraintData`. Data of one of these types that doesn't parse is an error rather
+/// than an unknown constraint
+const CONSTRAINT_TYPES: [&str; 11] = [
+    "StopPoint", "WptVelocityDirection", "WptZeroVelocity", "MaxVelocity", "MaxAngularVelocity",
+    "ZeroAngularVelocity", "StraightLine", "PointAt", "KeepInRectangle", "KeepInCircle", "KeepOutCircle",
+];
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", content = "props")]
+enum RawConstraintData {
+    StopPoint {},
+    WptVelocityDirection {
+        #[serde(deserialize_with = "angle_value")]
+        direction: f64,
+    },
+    WptZeroVelocity {},
+    MaxVelocity {
+        #[serde(deserialize_with = "velocity_value")]
+        max: f64,
+    },
+    MaxAngularVelocity {
+        #[serde(deserialize_with = "angular_velocity_value")]
+        max: f64,
+    },
+    ZeroAngularVelocity {},
+    StraightLine {},
+    PointAt {
+        #[serde(deserialize_with = "length_value")]
+        x: f64,
+        #[serde(deserialize_with = "length_value")]
+        y: f64,
+        #[serde(deserialize_with = "angle_value")]
+        tolerance: f64,
+        #[serde(default)]
+        flip: bool,
+    },
+    KeepInRectangle {
+        #[serde(deserialize_with = "length_value")]
+        x: f64,
+        #[serde(deserialize_with = "length_value")]
+        y: f64,
+        #[serde(deserialize_with = "length_value")]
+        w: f64,
+        #[serde(deserialize_with = "length_value")]
+        h: f64,
+    },
+    KeepInCircle {
+        #[serde(deserialize_with = "length_value")]
+        x: f64,
+        #[serde(deserialize_with = "length_value")]
+        y: f64,
+        #[serde(deserialize_with = "length_value")]
+        r: f64,
+    },
+    KeepOutCircle {
+        #[serde(deserialize_with = "length_value")]
+        x: f64,
+        #[serde(deserialize_with = "length_value")]
+        y: f64,
+        #[serde(deserialize_with = "length_value")]
+        r: f64,
+    },
+}
+
+impl TryFrom<RawConstraint> for Constraint {
+    type Error = serde_json::Error;
+
+    fn try_from(value: RawConstraint) -> Result<Self, Self::Error> {
+        let scope = match value.to {
+            Some(to) => ConstraintScope::Segment(value.from, to),
+            None => ConstraintScope::Waypoint(value.from),
+        };
+        let enabled = value.enabled;
+        let data = match value.data {
+            RawConstraintKind::Known(data) => data,
+            // Parse again for the error on why a modelled type didn't match
+            RawConstraintKind::Other { kind, props } if CONSTRAINT_TYPES.contains(&kind.as_str()) => {
+                serde_json::from_value(serde_json::json!({ "type": kind, "props": props }))?
+            }
+            RawConstraintKind::Other { kind, props } => return Ok(Constraint::Other { scope, enabled, kind, props }),
+        };
+        let m = Length::new::<meter>;
+        Ok(match data {
+            RawConstraintData::StopPoint {} => Constraint::StopPoint { scope, enabled },
+            RawConstraintData::WptVelocityDirection { direction } => Constraint::WptVelocityDirection { scope, enabled, direction: Angle::new::<radian>(direction) },
+            RawConstraintData::WptZeroVelocity {} => Constraint::WptZeroVelocity { scope, enabled },
+            RawConstraintData::MaxVelocity { max } => Constraint::MaxVelocity { scope, enabled, max: Velocity::new::<meter_per_second>(max) },
+            RawConstraintData::MaxAngularVelocity { max } => Constraint::MaxAngularVelocity { scope, enabled, max: AngularVelocity::new::<radian_per_second>(max) },
+            RawConstraintData::ZeroAngularVelocity {} => Constraint::ZeroAngularVelocity { scope, enabled },
+            RawConstraintData::StraightLine {} => Constraint::StraightLine { scope, enabled },
+            RawConstraintData::PointAt { x, y, tolerance, flip } => Constraint::PointAt { scope, enabled, x: m(x), y: m(y), tolerance: Angle::new::<radian>(tolerance), flip },
+            RawConstraintData::KeepInRectangle { x, y, w, h } => Constraint::KeepInRectangle { scope, enabled, x: m(x), y: m(y), w: m(w), h: m(h) },
+            RawConstraintData::KeepInCircle { x, y, r } => Constraint::KeepInCircle { scope, enabled, x: m(x), y: m(y), r: m(r) },
+            RawConstraintData::KeepOutCircle { x, y, r } => Constraint::KeepOutCircle { scope, enabled, x: m(x), y: m(y), r: m(r) },
+        })
+    }
+}
+
+impl From<Constraint> for RawConstraint {
+    fn from(value: Constraint) -> Self {
+        let (from, to) = match value.scope() {
+            ConstraintScope::Waypoint(wp) => (wp, None),
+            ConstraintScope::Segment(from, to) => (from, Some(to)),
+        };
+        let enabled = value.enabled();
+        let m = |l: Length| l.get::<meter>();
+        let data = match value {
+            Constraint::Other { kind, props, .. } => return RawConstraint { from, to, data: RawConstraintKind::Other { kind, props }, enabled },
+            Constraint::StopPoint { .. } => RawConstraintData::StopPoint {},
+            Constraint::WptVelocityDirection { direction, .. } => RawConstraintData::WptVelocityDirection { direction: direction.get::<radian>() },
+            Constraint::WptZeroVelocity { .. } => RawConstraintData::WptZeroVelocity {},
+            Constraint::MaxVelocity { max, .. } => RawConstraintData::MaxVelocity { max: max.get::<meter_per_second>() },
+            Constraint::MaxAngularVelocity { max, .. } => RawConstraintData::MaxAngularVelocity { max: max.get::<radian_per_second>() },
+            Constraint::ZeroAngularVelocity { .. } => RawConstraintData::ZeroAngularVelocity {},
+            Constraint::StraightLine { .. } => RawConstraintData::StraightLine {},
+            Constraint::PointAt { x, y, tolerance, flip, .. } => RawConstraintData::PointAt { x: m(x), y: m(y), tolerance: tolerance.get::<radian>(), flip },
+            Constraint::KeepInRectangle { x, y, w, h, .. } => RawConstraintData::KeepInRectangle { x: m(x), y: m(y), w: m(w), h: m(h) },
+            Constraint::KeepInCircle { x, y, r, .. } => RawConstraintData::KeepInCircle { x: m(x), y: m(y), r: m(r) },
+            Constraint::KeepOutCircle { x, y, r, .. } => RawConstraintData::KeepOutCircle { x: m(x), y: m(y), r: m(r) },
+        };
+        RawConstraint { from, to, data: RawConstraintKind::Known(data), enabled }
+    }
+}
+
+/// Robot configuration, evaluated from a Choreo project's config
+#[derive(Clone, Debug, PartialEq)]
 pub struct Params {
-    // TODO: implement
+    pub mass: Mass,
+    pub inertia: MomentOfInertia,
+    pub wheel_radius: Length,
+    /// After gearing
+    pub max_wheel_velocity: AngularVelocity,
+    /// After gearing
+    pub max_wheel_torque: Torque,
+    /// Front to back
+    pub bumper_length: Length,
+    /// Side to side
+    pub bumper_width: Length,
+    /// Module (or wheel) positions relative to the robot center, x forward and y to the left
+    pub modules: Vec<Translation2d>,
+    pub drive_type: DriveType,
 }
 
-#[derive(Serialize, Deserialize)]
+impl Params {
+    /// Max linear speed of a wheel at its contact patch
+    pub fn max_wheel_speed(&self) -> Velocity {
+        Velocity::new::<meter_per_second>(self.max_wheel_velocity.get::<radian_per_second>() * self.wheel_radius.get::<meter>())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DriveType {
+    #[default]
+    Swerve,
+    #[serde(alias = "DifferentialDrive")]
+    Differential,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Event {
-    // TODO: implement
+    pub name: String,
+    pub from: EventTiming,
+}
+
+/// When an event fires: an absolute timestamp, or an offset from a waypoint
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EventTiming {
+    /// Waypoint index the event is attached to
+    pub target: Option<usize>,
+    /// Timestamp of the target waypoint, or of the event itself when there is no target
+    #[serde(rename = "targetTimestamp")]
+    pub target_timestamp: Option<f64>,
+    /// Seconds after the target
+    #[serde(default, deserialize_with = "time_value")]
+    pub offset: f64,
+}
+
+impl Event {
+    /// Resolve the time this event fires at, given the timestamp of every waypoint
+    pub fn timestamp(&self, waypoint_times: &[f64]) -> Option<f64> {
+        let target = match self.from.target {
+            Some(i) => waypoint_times.get(i).copied().or(self.from.target_timestamp)?,
+            None => self.from.target_timestamp?,
+        };
+        Some(target + self.from.offset)
+    }
+}
+
+/// An event resolved to a point in time along a `Path`
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventMarker {
+    pub name: String,
+    pub timestamp: Time,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -75,16 +590,115 @@ pub struct Sample {
     pub velocity_y: f64,
     #[serde(rename = "omega")]
     pub angular_velocity: f64,
+    #[serde(rename = "ax", default, skip_serializing_if = "Option::is_none")]
+    pub acceleration_x: Option<f64>,
+    #[serde(rename = "ay", default, skip_serializing_if = "Option::is_none")]
+    pub acceleration_y: Option<f64>,
+    #[serde(rename = "alpha", default, skip_serializing_if = "Option::is_none")]
+    pub angular_acceleration: Option<f64>,
+    /// Per-module forces along field x and y, N. Only present in 2025 files
+    #[serde(rename = "fx", default, skip_serializing_if = "Vec::is_empty")]
+    pub module_forces_x: Vec<f64>,
+    #[serde(rename = "fy", default, skip_serializing_if = "Vec::is_empty")]
+    pub module_forces_y: Vec<f64>,
+}
+
+/// A 2025 sample in the layout of either drivetrain
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ChoreoSample {
+    Swerve(Sample),
+    Differential(DifferentialSample),
+}
+
+/// A sample of a differential drive trajectory, with wheel speeds in place of field velocity
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DifferentialSample {
+    pub t: f64,
+    pub x: f64,
+    pub y: f64,
+    pub heading: f64,
+    #[serde(rename = "vl")]
+    pub left_velocity: f64,
+    #[serde(rename = "vr")]
+    pub right_velocity: f64,
+    #[serde(rename = "omega")]
+    pub angular_velocity: f64,
+    #[serde(rename = "al", default, skip_serializing_if = "Option::is_none")]
+    pub left_acceleration: Option<f64>,
+    #[serde(rename = "ar", default, skip_serializing_if = "Option::is_none")]
+    pub right_acceleration: Option<f64>,
+    #[serde(rename = "alpha", default, skip_serializing_if = "Option::is_none")]
+    pub angular_acceleration: Option<f64>,
+    /// Left and right wheel forces, N
+    #[serde(rename = "fl", default, skip_serializing_if = "Option::is_none")]
+    pub left_force: Option<f64>,
+    #[serde(rename = "fr", default, skip_serializing_if = "Option::is_none")]
+    pub right_force: Option<f64>,
 }
 
+#[derive(Clone)]
 pub struct Path {
     samples: BTreeMap<NotNan<f64>, Pose>,
     waypoints: Vec<f64>,
+    waypoint_times: Vec<f64>,
+    constraints: Vec<Constraint>,
+    params: Option<Params>,
+    events: Vec<EventMarker>,
+    /// The file the path was loaded from, so saving it keeps fields this crate doesn't model
+    source: Option<serde_json::Value>,
+    extrapolation: Extrapolation,
+    interpolation: Interpolation,
+}
+
+/// How `Path::get` fills in poses between samples
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Linear in every field
+    #[default]
+    Linear,
+    /// Cubic Hermite for x, y and heading using the sample velocities, so position and velocity
+    /// are continuous. Smoother than linear for sparse samples
+    Hermite,
+}
+
+/// What `Path::get` returns for times before the start or after the end of the path
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Extrapolation {
+    /// The first or last pose, velocities included
+    #[default]
+    Clamp,
+    /// The first or last pose with zero velocity
+    Hold,
 }
 
 impl Path {
-    pub fn from_trajectory(trajectory: &str) -> Result<Self, serde_json::Error> {
-        let choreo = serde_json::from_str::<ChoreoTrajectory>(trajectory)?;
+    /// Load a Choreo .traj file. Both the 2024 and 2025 schemas are accepted
+    pub fn from_trajectory(trajectory: &str) -> Result<Self, TrajectoryError> {
+        Self::from_trajectory_with(trajectory, &Variables::default())
+    }
+
+    /// Load a Choreo .traj file, evaluating its expressions against `variables`, such as those of
+    /// `ChoreoProject::variables`. Expressions that can't be evaluated use their cached value
+    pub fn from_trajectory_with(trajectory: &str, variables: &Variables) -> Result<Self, TrajectoryError> {
+        let source = serde_json::from_str::<serde_json::Value>(trajectory)?;
+        let mut resolved = source.clone();
+        expr::resolve_values(&mut resolved, variables);
+        let mut path = match ChoreoFile::from_value(resolved)? {
+            ChoreoFile::V2024(choreo) => Self::from_choreo_2024(choreo)?,
+            ChoreoFile::V2025(choreo) => Self::from_choreo_2025(choreo)?,
+        };
+        path.source = Some(source);
+        Ok(path)
+    }
+
+    fn from_choreo_2024(choreo: ChoreoTrajectory) -> Result<Self, TrajectoryError> {
+        if choreo.snapshot.waypoints.len() != choreo.trajectory.waypoints.len() {
+            return Err(TrajectoryError::WaypointMismatch {
+                waypoints: choreo.snapshot.waypoints.len(),
+                timestamps: choreo.trajectory.waypoints.len(),
+            });
+        }
 
         let valid_waypoints = choreo.snapshot.waypoints
             .iter()
@@ -98,35 +712,182 @@ impl Path {
             waypoints: valid_waypoints,
         };
 
-        Ok(Self::from_trajectory_data(trajectory_data))
+        let mut path = Self::from_trajectory_data(trajectory_data)?;
+        path.set_choreo_metadata(choreo.trajectory.waypoints, choreo.snapshot.constraints, &choreo.events)?;
+        Ok(path)
     }
 
-    fn from_trajectory_data(data: TrajectoryData) -> Self {
+    fn from_choreo_2025(choreo: ChoreoTrajectory2025) -> Result<Self, TrajectoryError> {
+        let sample_type = match choreo.trajectory.sample_type.as_deref() {
+            None | Some("Swerve") => "Swerve",
+            Some("DifferentialDrive") => "DifferentialDrive",
+            Some(other) => return Err(TrajectoryError::UnknownSampleType(other.to_owned())),
+        };
+        let samples = choreo.trajectory.samples.into_iter().enumerate().map(|(index, sample)| match sample {
+            ChoreoSample::Swerve(sample) if sample_type == "Swerve" => Ok(sample),
+            ChoreoSample::Differential(sample) if sample_type == "DifferentialDrive" => Ok(sample.into()),
+            _ => Err(TrajectoryError::SampleTypeMismatch { index, sample_type }),
+        }).collect::<Result<Vec<Sample>, _>>()?;
+
+        if let Some(&split) = choreo.trajectory.splits.iter().find(|&&i| i >= samples.len()) {
+            return Err(TrajectoryError::SplitOutOfRange { split, samples: samples.len() });
+        }
+
+        // The first split always starts at sample 0, the rest start at a split waypoint
+        let valid_waypoints = choreo.trajectory.splits
+            .iter()
+            .filter(|&&i| i != 0)
+            .map(|&i| samples[i].t)
+            .collect();
+
+        let trajectory_data = TrajectoryData {
+            samples,
+            waypoints: valid_waypoints,
+        };
+
+        let mut path = Self::from_trajectory_data(trajectory_data)?;
+        path.set_choreo_metadata(choreo.trajectory.waypoints, choreo.snapshot.constraints, &choreo.events)?;
+        Ok(path)
+    }
+
+    fn set_choreo_metadata(&mut self, waypoint_times: Vec<f64>, constraints: Vec<Constraint>, events: &[Event]) -> Result<(), TrajectoryError> {
+        if let Some(index) = waypoint_times.iter().position(|t| !t.is_finite()) {
+            return Err(TrajectoryError::NonFinite { index, field: "waypoint timestamp" });
+        }
+
+        self.waypoint_times = waypoint_times;
+        self.constraints = constraints;
+        let markers = events.iter().enumerate().map(|(index, event)| {
+            let timestamp = event.timestamp(&self.waypoint_times).ok_or(TrajectoryError::UnresolvedEvent { index })?;
+            Ok(EventMarker { name: event.name.clone(), timestamp: Time::new::<second>(timestamp) })
+        }).collect::<Result<_, TrajectoryError>>()?;
+        self.set_events(markers);
+        Ok(())
+    }
+
+    /// Build a path from samples, which must be non-empty, finite and strictly increasing in time
+    fn from_trajectory_data(mut data: TrajectoryData) -> Result<Self, TrajectoryError> {
+        if data.samples.is_empty() {
+            return Err(TrajectoryError::Empty);
+        }
+
+        let mut previous: Option<f64> = None;
+        for (index, sample) in data.samples.iter().enumerate() {
+            sample.validate(index)?;
+            match previous {
+                Some(previous) if sample.t == previous => return Err(TrajectoryError::DuplicateTime { index, time: sample.t }),
+                Some(previous) if sample.t < previous => return Err(TrajectoryError::NonMonotonic { index, time: sample.t, previous }),
+                _ => previous = Some(sample.t),
+            }
+        }
+        fill_accelerations(&mut data.samples);
+
         let mut samples = BTreeMap::new();
         for sample in data.samples {
+            // Finite, checked above
             samples.insert(NotNan::new(sample.t).unwrap(), sample.into());
         }
-        Self {
-            samples,
-            waypoints: data.waypoints
+
+        if let Some(index) = data.waypoints.iter().position(|t| !t.is_finite()) {
+            return Err(TrajectoryError::NonFinite { index, field: "waypoint timestamp" });
         }
+
+        Ok(Self {
+            samples,
+            waypoints: data.waypoints,
+            waypoint_times: Vec::new(),
+            constraints: Vec::new(),
+            params: None,
+            events: Vec::new(),
+            source: None,
+            extrapolation: Extrapolation::default(),
+            interpolation: Interpolation::default(),
+        })
     }
 
+    fn set_events(&mut self, mut events: Vec<EventMarker>) {
+        events.sort_by(|a, b| a.timestamp.value.total_cmp(&b.timestamp.value));
+        self.events = events;
+    }
+
+    /// The pose at `elapsed`. Outside the path, or for NaN (treated as before the start), the
+    /// first or last pose is returned according to the path's `Extrapolation`
     pub fn get(&self, elapsed: Time) -> Pose {
-        let elapsed = elapsed.get::<second>();
-        let below = self.samples.upper_bound(std::ops::Bound::Included(&NotNan::new(elapsed).unwrap()));
+        if let Some(pose) = self.try_get(elapsed) {
+            return pose;
+        }
 
-        if let Some(above) = below.peek_next() {
-            let below_time = **below.key().unwrap();
-            let above_time = **above.0;
-            let progress = (elapsed - below_time) / (above_time - below_time);
+        let after_end = elapsed.get::<second>() > **self.samples.first_key_value().unwrap().0;
+        let pose = if after_end { self.samples.last_key_value() } else { self.samples.first_key_value() };
+        let pose = pose.unwrap().1;
+        match self.extrapolation {
+            Extrapolation::Clamp => pose.clone(),
+            Extrapolation::Hold => pose.stopped(),
+        }
+    }
 
-            below.value().unwrap().lerp(above.1, progress)
-        } else {
-            below.value().unwrap().to_owned()
+    /// The pose at `elapsed`, or `None` if it's outside the path or NaN
+    pub fn try_get(&self, elapsed: Time) -> Option<Pose> {
+        let elapsed = NotNan::new(elapsed.get::<second>()).ok()?;
+        let (below_time, below) = self.samples.range(..=elapsed).next_back()?;
+
+        match self.samples.range((Bound::Excluded(elapsed), Bound::Unbounded)).next() {
+            Some((above_time, above)) => {
+                let dt = **above_time - **below_time;
+                let progress = (*elapsed - **below_time) / dt;
+                Some(match self.interpolation {
+                    Interpolation::Linear => below.lerp(above, progress),
+                    Interpolation::Hermite => below.hermite(above, dt, progress),
+                })
+            }
+            None if elapsed == *below_time => Some(below.clone()),
+            None => None,
+        }
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Field-relative jerk at `elapsed`: the change in acceleration between the samples around it
+    pub fn jerk(&self, elapsed: Time) -> (Jerk, Jerk) {
+        let zero = Jerk::new::<meter_per_second_cubed>(0.);
+        let Ok(elapsed) = NotNan::new(elapsed.get::<second>()) else {
+            return (zero, zero);
+        };
+        let below = self.samples.range(..=elapsed).next_back();
+        let above = self.samples.range((Bound::Excluded(elapsed), Bound::Unbounded)).next();
+        match (below, above) {
+            (Some((below_time, below)), Some((above_time, above))) => {
+                let dt = Time::new::<second>(**above_time - **below_time);
+                ((above.acceleration_x - below.acceleration_x) / dt, (above.acceleration_y - below.acceleration_y) / dt)
+            }
+            _ => (zero, zero),
         }
     }
 
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.extrapolation
+    }
+
+    pub fn set_extrapolation(&mut self, extrapolation: Extrapolation) {
+        self.extrapolation = extrapolation;
+    }
+
+    pub fn with_extrapolation(mut self, extrapolation: Extrapolation) -> Self {
+        self.extrapolation = extrapolation;
+        self
+    }
+
     pub fn length(&self) -> Time {
         Time::new::<second>(**self.samples.last_key_value().unwrap().0)
     }
@@ -135,8 +896,46 @@ impl Path {
     pub fn waypoints(&self) -> &[f64] {
         &self.waypoints
     }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Robot configuration the path was generated for, when it was loaded through a
+    /// `ChoreoProject`
+    pub fn params(&self) -> Option<&Params> {
+        self.params.as_ref()
+    }
+
+    /// Event markers, sorted by timestamp
+    pub fn events(&self) -> &[EventMarker] {
+        &self.events
+    }
+
+    /// Events in `[previous, current)`. Calling this every loop with the last and current
+    /// elapsed time fires each event exactly once
+    pub fn events_between(&self, previous: Time, current: Time) -> impl Iterator<Item = &EventMarker> + '_ {
+        self.events.iter()
+            .skip_while(move |event| event.timestamp < previous)
+            .take_while(move |event| event.timestamp < current)
+    }
+
+    /// Enabled constraints whose scope covers `elapsed`. Waypoint constraints only match at the waypoint's exact timestamp
+    pub fn constraints_at(&self, elapsed: Time) -> impl Iterator<Item = &Constraint> + '_ {
+        let elapsed = elapsed.get::<second>();
+        self.constraints.iter().filter(move |constraint| {
+            constraint.enabled()
+                && constraint.scope()
+                    .time_range(&self.waypoint_times)
+                    .is_some_and(|(from, to)| from <= elapsed && elapsed <= to)
+        })
+    }
 }
 
+/// The robot's state at one point along a path: where it is, field-relative velocity and
+/// acceleration. Position and heading stay plain fields so they can be read and set directly;
+/// `pose2d`, `translation` and `rotation` give them as geometry types for composing and
+/// transforming
 #[derive(Clone, Debug)]
 pub struct Pose {
     pub x: Length,
@@ -145,31 +944,98 @@ pub struct Pose {
     pub angular_velocity: AngularVelocity,
     pub velocity_x: Velocity,
     pub velocity_y: Velocity,
+    pub angular_acceleration: AngularAcceleration,
+    pub acceleration_x: Acceleration,
+    pub acceleration_y: Acceleration,
 }
 
 impl Pose {
+    fn stopped(&self) -> Pose {
+        Pose {
+            angular_velocity: AngularVelocity::new::<radian_per_second>(0.),
+            velocity_x: Velocity::new::<meter_per_second>(0.),
+            velocity_y: Velocity::new::<meter_per_second>(0.),
+            angular_acceleration: AngularAcceleration::new::<radian_per_second_squared>(0.),
+            acceleration_x: Acceleration::new::<meter_per_second_squared>(0.),
+            acceleration_y: Acceleration::new::<meter_per_second_squared>(0.),
+            ..self.clone()
+        }
+    }
+
     fn lerp(&self, other: &Pose, l: f64) -> Pose {
         Pose {
             x: lerp(self.x, other.x, l),
             y: lerp(self.y, other.y, l),
-            heading: lerp(self.heading, other.heading, l),
+            heading: lerp_angle(self.heading, other.heading, l),
             angular_velocity: lerp(self.angular_velocity, other.angular_velocity, l),
             velocity_x: lerp(self.velocity_x, other.velocity_x, l),
             velocity_y: lerp(self.velocity_y, other.velocity_y, l),
+            angular_acceleration: lerp(self.angular_acceleration, other.angular_acceleration, l),
+            acceleration_x: lerp(self.acceleration_x, other.acceleration_x, l),
+            acceleration_y: lerp(self.acceleration_y, other.acceleration_y, l),
         }
     }
 
-    /// X and Y are half of the field length and width
-    /// velocity might be wrong
-    pub fn mirror(&self, x: Length, y: Length) -> Pose {
-        let target_heading = if self.heading.get::<radian>() < 0. { self.heading + Angle::new::<radian>(PI) } else { self.heading - Angle::new::<radian>(PI) };
+    /// Cubic Hermite interpolation to `other`, `dt` seconds later. Velocities and accelerations
+    /// are derivatives of the interpolated position
+    fn hermite(&self, other: &Pose, dt: f64, l: f64) -> Pose {
+        let (x, velocity_x, acceleration_x) = hermite(
+            self.x.get::<meter>(), self.velocity_x.get::<meter_per_second>(),
+            other.x.get::<meter>(), other.velocity_x.get::<meter_per_second>(), dt, l);
+        let (y, velocity_y, acceleration_y) = hermite(
+            self.y.get::<meter>(), self.velocity_y.get::<meter_per_second>(),
+            other.y.get::<meter>(), other.velocity_y.get::<meter_per_second>(), dt, l);
+        let heading = self.heading.get::<radian>();
+        let (heading, angular_velocity, angular_acceleration) = hermite(
+            heading, self.angular_velocity.get::<radian_per_second>(),
+            heading + angle_difference(self.heading, other.heading).get::<radian>(),
+            other.angular_velocity.get::<radian_per_second>(), dt, l);
+
         Pose {
-            x: x - self.x + x,
-            y: y - self.y + y,
-            heading: target_heading,
-            angular_velocity: -self.angular_velocity,
-            velocity_x: self.velocity_x,
-            velocity_y: self.velocity_y,
+            x: Length::new::<meter>(x),
+            y: Length::new::<meter>(y),
+            heading: Angle::new::<radian>(heading),
+            angular_velocity: AngularVelocity::new::<radian_per_second>(angular_velocity),
+            velocity_x: Velocity::new::<meter_per_second>(velocity_x),
+            velocity_y: Velocity::new::<meter_per_second>(velocity_y),
+            angular_acceleration: AngularAcceleration::new::<radian_per_second_squared>(angular_acceleration),
+            acceleration_x: Acceleration::new::<meter_per_second_squared>(acceleration_x),
+            acceleration_y: Acceleration::new::<meter_per_second_squared>(acceleration_y),
+        }
+    }
+
+    /// The pose for the other alliance, using the field's symmetry
+    pub fn mirror(&self, field: &Field) -> Pose {
+        self.flipped(field, field.symmetry)
+    }
+
+    /// The pose on the other alliance's side of `field`. Velocities and accelerations are
+    /// transformed the same way as the position, so feedforward still matches the motion
+    pub fn flipped(&self, field: &Field, symmetry: Symmetry) -> Pose {
+        let (center_x, center_y) = (field.length / 2., field.width / 2.);
+        match symmetry {
+            Symmetry::Rotational => Pose {
+                x: center_x - self.x + center_x,
+                y: center_y - self.y + center_y,
+                heading: if self.heading.get::<radian>() < 0. { self.heading + Angle::new::<radian>(PI) } else { self.heading - Angle::new::<radian>(PI) },
+                angular_velocity: self.angular_velocity,
+                velocity_x: -self.velocity_x,
+                velocity_y: -self.velocity_y,
+                angular_acceleration: self.angular_acceleration,
+                acceleration_x: -self.acceleration_x,
+                acceleration_y: -self.acceleration_y,
+            },
+            Symmetry::Mirrored => Pose {
+                x: center_x - self.x + center_x,
+                y: self.y,
+                heading: normalize_angle(Angle::new::<radian>(PI) - self.heading),
+                angular_velocity: -self.angular_velocity,
+                velocity_x: -self.velocity_x,
+                velocity_y: self.velocity_y,
+                angular_acceleration: -self.angular_acceleration,
+                acceleration_x: -self.acceleration_x,
+                acceleration_y: self.acceleration_y,
+            },
         }
     }
 }
@@ -179,6 +1045,64 @@ where A: Sub<A, Output=A> + Add<A, Output=A> + Mul<f64, Output = A> + Clone {
     a.clone() + (b - a) * l
 }
 
+/// Cubic Hermite value, first and second derivative at `l` between `p0` and `p1`, with
+/// derivatives `v0` and `v1`, `dt` apart
+fn hermite(p0: f64, v0: f64, p1: f64, v1: f64, dt: f64, l: f64) -> (f64, f64, f64) {
+    let (l2, l3) = (l * l, l * l * l);
+    let position = (2. * l3 - 3. * l2 + 1.) * p0
+        + (l3 - 2. * l2 + l) * dt * v0
+        + (-2. * l3 + 3. * l2) * p1
+        + (l3 - l2) * dt * v1;
+    let velocity = ((6. * l2 - 6. * l) * p0
+        + (3. * l2 - 4. * l + 1.) * dt * v0
+        + (-6. * l2 + 6. * l) * p1
+        + (3. * l2 - 2. * l) * dt * v1) / dt;
+    let acceleration = ((12. * l - 6.) * p0
+        + (6. * l - 4.) * dt * v0
+        + (-12. * l + 6.) * p1
+        + (6. * l - 2.) * dt * v1) / (dt * dt);
+    (position, velocity, acceleration)
+}
+
+/// Interpolate the short way around, so headings either side of ±π don't spin through 0
+fn lerp_angle(a: Angle, b: Angle, l: f64) -> Angle {
+    a + angle_difference(a, b) * l
+}
+
+/// Wrap an angle into [-π, π)
+pub fn normalize_angle(angle: Angle) -> Angle {
+    Angle::new::<radian>((angle.get::<radian>() + PI).rem_euclid(2. * PI) - PI)
+}
+
+/// Signed rotation from `from` to `to` the short way around, in [-π, π)
+pub fn angle_difference(from: Angle, to: Angle) -> Angle {
+    normalize_angle(to - from)
+}
+
+/// Fill in missing accelerations by differentiating the velocities of neighbouring samples.
+/// Samples must be in strictly increasing time order
+fn fill_accelerations(samples: &mut [Sample]) {
+    let derivatives: Vec<(f64, f64, f64)> = (0..samples.len()).map(|i| {
+        let prev = &samples[i.saturating_sub(1)];
+        let next = &samples[(i + 1).min(samples.len() - 1)];
+        let dt = next.t - prev.t;
+        if dt <= 0. {
+            return (0., 0., 0.);
+        }
+        (
+            (next.velocity_x - prev.velocity_x) / dt,
+            (next.velocity_y - prev.velocity_y) / dt,
+            (next.angular_velocity - prev.angular_velocity) / dt,
+        )
+    }).collect();
+
+    for (sample, (ax, ay, alpha)) in samples.iter_mut().zip(derivatives) {
+        sample.acceleration_x.get_or_insert(ax);
+        sample.acceleration_y.get_or_insert(ay);
+        sample.angular_acceleration.get_or_insert(alpha);
+    }
+}
+
 impl From<Sample> for Pose {
     fn from(value: Sample) -> Self {
         Self {
@@ -188,10 +1112,105 @@ impl From<Sample> for Pose {
             angular_velocity: AngularVelocity::new::<radian_per_second>(value.angular_velocity),
             velocity_x: Velocity::new::<meter_per_second>(value.velocity_x),
             velocity_y: Velocity::new::<meter_per_second>(value.velocity_y),
+            angular_acceleration: AngularAcceleration::new::<radian_per_second_squared>(value.angular_acceleration.unwrap_or(0.)),
+            acceleration_x: Acceleration::new::<meter_per_second_squared>(value.acceleration_x.unwrap_or(0.)),
+            acceleration_y: Acceleration::new::<meter_per_second_squared>(value.acceleration_y.unwrap_or(0.)),
+        }
+    }
+}
+
+impl Sample {
+    fn validate(&self, index: usize) -> Result<(), TrajectoryError> {
+        let fields = [
+            (self.t, "t"),
+            (self.x, "x"),
+            (self.y, "y"),
+            (self.heading, "heading"),
+            (self.velocity_x, "vx"),
+            (self.velocity_y, "vy"),
+            (self.angular_velocity, "omega"),
+            (self.acceleration_x.unwrap_or(0.), "ax"),
+            (self.acceleration_y.unwrap_or(0.), "ay"),
+            (self.angular_acceleration.unwrap_or(0.), "alpha"),
+        ];
+        match fields.iter().find(|(value, _)| !value.is_finite()) {
+            Some(&(_, field)) => Err(TrajectoryError::NonFinite { index, field }),
+            None => Ok(()),
+        }
+    }
+
+    pub fn from_pose(t: f64, pose: &Pose) -> Self {
+        Self {
+            t,
+            x: pose.x.get::<meter>(),
+            y: pose.y.get::<meter>(),
+            heading: pose.heading.get::<radian>(),
+            velocity_x: pose.velocity_x.get::<meter_per_second>(),
+            velocity_y: pose.velocity_y.get::<meter_per_second>(),
+            angular_velocity: pose.angular_velocity.get::<radian_per_second>(),
+            acceleration_x: Some(pose.acceleration_x.get::<meter_per_second_squared>()),
+            acceleration_y: Some(pose.acceleration_y.get::<meter_per_second_squared>()),
+            angular_acceleration: Some(pose.angular_acceleration.get::<radian_per_second_squared>()),
+            module_forces_x: Vec::new(),
+            module_forces_y: Vec::new(),
+        }
+    }
+}
+
+impl DifferentialSample {
+    /// The sample for a pose, with the wheels `half_track_width` meters either side of the center
+    pub fn from_pose(t: f64, pose: &Pose, half_track_width: f64) -> Self {
+        let (cos, sin) = (pose.heading.get::<radian>().cos(), pose.heading.get::<radian>().sin());
+        let velocity = pose.velocity_x.get::<meter_per_second>() * cos + pose.velocity_y.get::<meter_per_second>() * sin;
+        let acceleration = pose.acceleration_x.get::<meter_per_second_squared>() * cos
+            + pose.acceleration_y.get::<meter_per_second_squared>() * sin;
+        let omega = pose.angular_velocity.get::<radian_per_second>();
+        let alpha = pose.angular_acceleration.get::<radian_per_second_squared>();
+        Self {
+            t,
+            x: pose.x.get::<meter>(),
+            y: pose.y.get::<meter>(),
+            heading: pose.heading.get::<radian>(),
+            left_velocity: velocity - omega * half_track_width,
+            right_velocity: velocity + omega * half_track_width,
+            angular_velocity: omega,
+            left_acceleration: Some(acceleration - alpha * half_track_width),
+            right_acceleration: Some(acceleration + alpha * half_track_width),
+            angular_acceleration: Some(alpha),
+            left_force: None,
+            right_force: None,
+        }
+    }
+}
+
+/// Field-relative velocity along the heading, from the average of the wheel speeds. The
+/// acceleration adds the centripetal term of turning while moving
+impl From<DifferentialSample> for Sample {
+    fn from(value: DifferentialSample) -> Self {
+        let (cos, sin) = (value.heading.cos(), value.heading.sin());
+        let velocity = (value.left_velocity + value.right_velocity) / 2.;
+        let centripetal = velocity * value.angular_velocity;
+        let acceleration = value.left_acceleration.zip(value.right_acceleration).map(|(left, right)| (left + right) / 2.);
+        Self {
+            t: value.t,
+            x: value.x,
+            y: value.y,
+            heading: value.heading,
+            velocity_x: velocity * cos,
+            velocity_y: velocity * sin,
+            angular_velocity: value.angular_velocity,
+            acceleration_x: acceleration.map(|acceleration| acceleration * cos - centripetal * sin),
+            acceleration_y: acceleration.map(|acceleration| acceleration * sin + centripetal * cos),
+            angular_acceleration: value.angular_acceleration,
+            module_forces_x: Vec::new(),
+            module_forces_y: Vec::new(),
         }
     }
 }
 
+#[cfg(test)]
+use uom::si::angle::degree;
+
 #[test]
 fn parse() {
     let data = include_str!("../../RobotCode2025/auto/Blue2.traj");
@@ -208,9 +1227,234 @@ fn mirror_test() {
 
     println!("{:?}", setpoint);
 
-    let setpoint = setpoint.mirror(Length::new::<meter>(17.55 / 2.), Length::new::<meter>(8.05 / 2.));
+    let setpoint = setpoint.mirror(&Field::season(2025).unwrap());
 
-    assert_eq!(setpoint.x.get::<meter>(), 9.53808);
-    assert_eq!(setpoint.y.get::<meter>(), 0.4438400000000007);
+    assert!((setpoint.x.get::<meter>() - 9.53608).abs() < 1e-9);
+    assert!((setpoint.y.get::<meter>() - 0.44584).abs() < 1e-9);
     assert_eq!(setpoint.heading.get::<degree>(), 180.);
 }
+
+#[test]
+fn constraint_parse() {
+    let data = r#"[
+        {"from": "first", "to": null, "data": {"type": "StopPoint", "props": {}}},
+        {"from": 1, "to": "last", "data": {"type": "MaxVelocity", "props": {"max": 2.5}}},
+        {"from": 0, "to": 1, "data": {"type": "StraightLine", "props": {}}, "enabled": false},
+        {"from": 0, "to": 1, "data": {"type": "MaxAcceleration", "props": {"max": {"exp": "3 m / s ^ 2", "val": 3.0}}}}
+    ]"#;
+    let constraints = serde_json::from_str::<Vec<Constraint>>(data).unwrap();
+
+    assert_eq!(constraints[0], Constraint::StopPoint { scope: ConstraintScope::Waypoint(WaypointId::First), enabled: true });
+    assert_eq!(constraints[1].scope(), ConstraintScope::Segment(WaypointId::Index(1), WaypointId::Last));
+    assert_eq!(constraints[1].scope().time_range(&[0., 1.2, 3.4]), Some((1.2, 3.4)));
+    assert!(!constraints[2].enabled());
+    assert_eq!(serde_json::to_value(&constraints[2]).unwrap()["enabled"], false);
+    assert!(matches!(&constraints[3], Constraint::Other { kind, .. } if kind == "MaxAcceleration"));
+    let saved = serde_json::to_value(&constraints[3]).unwrap();
+    assert_eq!(saved["data"], serde_json::from_str::<serde_json::Value>(data).unwrap()[3]["data"]);
+    assert!(serde_json::from_str::<Constraint>(r#"{"from": 0, "to": 1, "data": {"type": "MaxVelocity", "props": {}}}"#).is_err());
+}
+
+#[test]
+fn event_timestamp() {
+    let data = r#"[
+        {"name": "intake", "from": {"target": 1, "targetTimestamp": 1.0, "offset": 0.25}},
+        {"name": "shoot", "from": {"target": null, "targetTimestamp": 2.0}}
+    ]"#;
+    let events = serde_json::from_str::<Vec<Event>>(data).unwrap();
+
+    assert_eq!(events[0].timestamp(&[0., 1.5]), Some(1.75));
+    assert_eq!(events[1].timestamp(&[0., 1.5]), Some(2.0));
+}
+
+#[cfg(test)]
+const TEST_TRAJ_2025: &str = r#"{
+    "name": "Test",
+    "version": "v2025.0.0",
+    "snapshot": {
+        "waypoints": [
+            {"x": 1.0, "y": 1.0, "heading": 0.0, "intervals": 2, "split": false, "fixTranslation": true, "fixHeading": true, "overrideIntervals": false},
+            {"x": 2.0, "y": 1.0, "heading": 0.0, "intervals": 2, "split": true, "fixTranslation": true, "fixHeading": true, "overrideIntervals": false},
+            {"x": 3.0, "y": 1.0, "heading": 0.0, "intervals": 2, "split": false, "fixTranslation": true, "fixHeading": true, "overrideIntervals": false}
+        ],
+        "constraints": [{"from": "first", "to": null, "data": {"type": "StopPoint", "props": {}}, "enabled": true}],
+        "targetDt": 0.05
+    },
+    "params": {
+        "waypoints": [],
+        "constraints": [{"from": 0, "to": 1, "data": {"type": "MaxVelocity", "props": {"max": {"exp": "2 m / s", "val": 2.0}}}, "enabled": true}],
+        "targetDt": {"exp": "0.05 s", "val": 0.05}
+    },
+    "trajectory": {
+        "sampleType": "Swerve",
+        "waypoints": [0.0, 1.0, 2.0],
+        "samples": [
+            {"t": 0.0, "x": 1.0, "y": 1.0, "heading": 0.0, "vx": 0.0, "vy": 0.0, "omega": 0.0, "ax": 0.0, "ay": 0.0, "alpha": 0.0, "fx": [0, 0, 0, 0], "fy": [0, 0, 0, 0]},
+            {"t": 1.0, "x": 2.0, "y": 1.0, "heading": 0.0, "vx": 1.0, "vy": 0.0, "omega": 0.0, "ax": 0.0, "ay": 0.0, "alpha": 0.0, "fx": [0, 0, 0, 0], "fy": [0, 0, 0, 0]},
+            {"t": 2.0, "x": 3.0, "y": 1.0, "heading": 0.0, "vx": 0.0, "vy": 0.0, "omega": 0.0, "ax": 0.0, "ay": 0.0, "alpha": 0.0, "fx": [0, 0, 0, 0], "fy": [0, 0, 0, 0]}
+        ],
+        "splits": [0, 1]
+    },
+    "events": [{"name": "intake", "from": {"target": 1, "targetTimestamp": 1.0, "offset": {"exp": "0.5 s", "val": 0.5}}, "event": null}]
+}"#;
+
+#[test]
+fn parse_2025() {
+    let data = TEST_TRAJ_2025;
+    let path = Path::from_trajectory(data).unwrap();
+
+    assert_eq!(path.waypoints(), &[1.0]);
+    assert_eq!(path.length().get::<second>(), 2.0);
+    assert_eq!(path.events()[0].timestamp.get::<second>(), 1.5);
+    assert_eq!(path.constraints().len(), 1);
+    assert!(path.params().is_none());
+}
+
+#[test]
+fn parse_with_variables() {
+    let data = TEST_TRAJ_2025.replace(r#"{"exp": "0.5 s", "val": 0.5}"#, r#"{"exp": "delay", "val": 0.9}"#);
+    let mut variables = Variables::default();
+    variables.insert("delay", Expr::new("250 ms"));
+
+    let path = Path::from_trajectory_with(&data, &variables).unwrap();
+    assert_eq!(path.events()[0].timestamp.get::<second>(), 1.25);
+    // Without the variable the stale cached value is all there is
+    let path = Path::from_trajectory(&data).unwrap();
+    assert_eq!(path.events()[0].timestamp.get::<second>(), 1.9);
+    // The source file is kept as written
+    assert_eq!(path.to_choreo("Test").unwrap()["events"][0]["from"]["offset"]["val"], 0.9);
+
+    variables.insert("delay", Expr::new("25 cm"));
+    assert!(Path::from_trajectory_with(&data, &variables).is_err());
+}
+
+#[test]
+fn unresolved_event() {
+    let data = TEST_TRAJ_2025.replace(r#""target": 1, "targetTimestamp": 1.0"#, r#""target": 7, "targetTimestamp": null"#);
+    assert!(matches!(Path::from_trajectory(&data), Err(TrajectoryError::UnresolvedEvent { index: 0 })));
+}
+
+#[test]
+fn parse_numeric_version() {
+    let data = r#"{
+        "name": "Test",
+        "version": 1,
+        "snapshot": {
+            "waypoints": [
+                {"x": 1.0, "y": 1.0, "heading": 0.0, "intervals": 2, "split": false, "fixTranslation": true, "fixHeading": true, "overrideIntervals": false},
+                {"x": 2.0, "y": 1.0, "heading": 0.0, "intervals": 2, "split": false, "fixTranslation": true, "fixHeading": true, "overrideIntervals": false}
+            ],
+            "constraints": [{"from": "first", "to": "last", "data": {"type": "MaxVelocity", "props": {"max": 2.0}}, "enabled": true}],
+            "targetDt": 0.05
+        },
+        "params": {
+            "waypoints": [
+                {"x": {"exp": "1 m", "val": 1.0}, "y": {"exp": "1 m", "val": 1.0}, "heading": {"exp": "0 rad", "val": 0.0}, "intervals": 2, "split": false, "fixTranslation": true, "fixHeading": true, "overrideIntervals": false},
+                {"x": {"exp": "2 m", "val": 2.0}, "y": {"exp": "1 m", "val": 1.0}, "heading": {"exp": "0 rad", "val": 0.0}, "intervals": 2, "split": false, "fixTranslation": true, "fixHeading": true, "overrideIntervals": false}
+            ],
+            "constraints": [{"from": "first", "to": "last", "data": {"type": "MaxVelocity", "props": {"max": {"exp": "2 m / s", "val": 2.0}}}, "enabled": true}],
+            "targetDt": {"exp": "0.05 s", "val": 0.05}
+        },
+        "trajectory": {
+            "waypoints": [0.0, 1.0],
+            "samples": [
+                {"t": 0.0, "x": 1.0, "y": 1.0, "heading": 0.0, "vx": 0.0, "vy": 0.0, "omega": 0.0},
+                {"t": 1.0, "x": 2.0, "y": 1.0, "heading": 0.0, "vx": 0.0, "vy": 0.0, "omega": 0.0}
+            ]
+        },
+        "events": []
+    }"#;
+    let path = Path::from_trajectory(data).unwrap();
+
+    assert_eq!(path.length().get::<second>(), 1.0);
+    assert_eq!(path.constraints().len(), 1);
+    assert!(path.params().is_none());
+}
+
+#[test]
+fn invalid_samples() {
+    let load = |samples: &str| Path::from_trajectory_data(TrajectoryData {
+        samples: serde_json::from_str(samples).unwrap(),
+        waypoints: Vec::new(),
+    });
+
+    assert!(matches!(load("[]"), Err(TrajectoryError::Empty)));
+    assert!(matches!(
+        load(r#"[{"t": 0.0, "x": 0, "y": 0, "heading": 0, "vx": 0, "vy": 0, "omega": 0},
+                 {"t": 0.0, "x": 0, "y": 0, "heading": 0, "vx": 0, "vy": 0, "omega": 0}]"#),
+        Err(TrajectoryError::DuplicateTime { index: 1, .. })
+    ));
+    assert!(matches!(
+        load(r#"[{"t": 1.0, "x": 0, "y": 0, "heading": 0, "vx": 0, "vy": 0, "omega": 0},
+                 {"t": 0.5, "x": 0, "y": 0, "heading": 0, "vx": 0, "vy": 0, "omega": 0}]"#),
+        Err(TrajectoryError::NonMonotonic { index: 1, .. })
+    ));
+}
+
+#[test]
+fn heading_wraps() {
+    let a = Pose {
+        x: Length::new::<meter>(0.),
+        y: Length::new::<meter>(0.),
+        heading: Angle::new::<degree>(179.),
+        angular_velocity: AngularVelocity::new::<radian_per_second>(0.),
+        velocity_x: Velocity::new::<meter_per_second>(0.),
+        velocity_y: Velocity::new::<meter_per_second>(0.),
+        angular_acceleration: AngularAcceleration::new::<radian_per_second_squared>(0.),
+        acceleration_x: Acceleration::new::<meter_per_second_squared>(0.),
+        acceleration_y: Acceleration::new::<meter_per_second_squared>(0.),
+    };
+    let b = Pose { heading: Angle::new::<degree>(-179.), ..a.clone() };
+
+    let middle = a.lerp(&b, 0.5);
+    assert!((normalize_angle(middle.heading).get::<degree>().abs() - 180.).abs() < 1e-9);
+    assert!((angle_difference(a.heading, b.heading).get::<degree>() - 2.).abs() < 1e-9);
+}
+
+#[test]
+fn hermite_interpolation() {
+    let path = Path::from_trajectory(TEST_TRAJ_2025).unwrap().with_interpolation(Interpolation::Hermite);
+
+    // Samples and their velocities are matched exactly
+    let sample = path.get(Time::new::<second>(1.0));
+    assert!((sample.x.get::<meter>() - 2.0).abs() < 1e-9);
+    assert!((sample.velocity_x.get::<meter_per_second>() - 1.0).abs() < 1e-9);
+
+    // Accelerating from rest, so the robot is behind the linear midpoint
+    let middle = path.get(Time::new::<second>(0.5));
+    assert!((middle.x.get::<meter>() - 1.375).abs() < 1e-9);
+}
+
+#[test]
+fn get_outside_path() {
+    let path = Path::from_trajectory(TEST_TRAJ_2025).unwrap();
+
+    let before = path.get(Time::new::<second>(-1.0));
+    assert_eq!(before.x.get::<meter>(), 1.0);
+    assert!(path.try_get(Time::new::<second>(f64::NAN)).is_none());
+    assert_eq!(path.get(Time::new::<second>(f64::NAN)).x.get::<meter>(), 1.0);
+
+    let path = path.with_extrapolation(Extrapolation::Hold);
+    assert!(path.try_get(Time::new::<second>(3.0)).is_none());
+    let after = path.get(Time::new::<second>(3.0));
+    assert_eq!(after.x.get::<meter>(), 3.0);
+    assert_eq!(after.velocity_x.get::<meter_per_second>(), 0.0);
+}
+
+#[test]
+fn finite_difference_accelerations() {
+    let path = Path::from_trajectory_data(TrajectoryData {
+        samples: serde_json::from_str(r#"[
+            {"t": 0.0, "x": 0.0, "y": 0, "heading": 0, "vx": 0.0, "vy": 0, "omega": 0},
+            {"t": 1.0, "x": 0.5, "y": 0, "heading": 0, "vx": 1.0, "vy": 0, "omega": 0},
+            {"t": 2.0, "x": 1.5, "y": 0, "heading": 0, "vx": 1.0, "vy": 0, "omega": 0, "ax": 0.5}
+        ]"#).unwrap(),
+        waypoints: Vec::new(),
+    }).unwrap();
+
+    let acceleration = |t: f64| path.get(Time::new::<second>(t)).acceleration_x.get::<meter_per_second_squared>();
+    assert_eq!(acceleration(0.0), 1.0);
+    assert_eq!(acceleration(1.0), 0.5);
+    assert_eq!(acceleration(2.0), 0.5);
+    assert_eq!(path.jerk(Time::new::<second>(0.5)).0.get::<meter_per_second_cubed>(), -0.5);
+}
@@ -0,0 +1,461 @@
+use std::fmt;
+use std::fs;
+use std::path::Path as FsPath;
+use serde::{Serialize, Deserialize};
+use uom::si::f64::{Angle, Time};
+use uom::si::{angle::radian, time::second};
+use crate::{angle_difference, normalize_angle, EventMarker, Path, Sample, TrajectoryData, TrajectoryError};
+
+/// Points sampled along every bezier segment when generating a trajectory
+const SAMPLES_PER_SEGMENT: usize = 100;
+
+/// A PathPlanner .path file. PathPlanner only stores the spline and its constraints, so
+/// `to_path` time-parameterizes it with a trapezoidal velocity profile
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PathPlannerPath {
+    pub waypoints: Vec<PathPlannerWaypoint>,
+    #[serde(default)]
+    pub rotation_targets: Vec<RotationTarget>,
+    #[serde(default)]
+    pub constraint_zones: Vec<ConstraintZone>,
+    #[serde(default)]
+    pub event_markers: Vec<PathPlannerEventMarker>,
+    pub global_constraints: PathConstraints,
+    pub goal_end_state: PathState,
+    pub ideal_starting_state: Option<PathState>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PathPlannerWaypoint {
+    pub anchor: Point,
+    pub prev_control: Option<Point>,
+    pub next_control: Option<Point>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RotationTarget {
+    pub waypoint_relative_pos: f64,
+    /// Degrees
+    pub rotation_degrees: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstraintZone {
+    pub min_waypoint_relative_pos: f64,
+    pub max_waypoint_relative_pos: f64,
+    pub constraints: PathConstraints,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PathPlannerEventMarker {
+    pub name: String,
+    pub waypoint_relative_pos: f64,
+}
+
+/// Only the translational limits are used for generation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PathConstraints {
+    /// m/s
+    pub max_velocity: f64,
+    /// m/s^2
+    pub max_acceleration: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PathState {
+    /// m/s
+    pub velocity: f64,
+    /// Degrees
+    pub rotation: f64,
+}
+
+/// Why an auto's paths couldn't be loaded
+#[derive(Debug)]
+pub enum PathPlannerError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Trajectory(TrajectoryError),
+}
+
+impl fmt::Display for PathPlannerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathPlannerError::Io(err) => write!(f, "failed to read path: {}", err),
+            PathPlannerError::Parse(err) => write!(f, "failed to parse path: {}", err),
+            PathPlannerError::Trajectory(err) => write!(f, "failed to generate trajectory: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PathPlannerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PathPlannerError::Io(err) => Some(err),
+            PathPlannerError::Parse(err) => Some(err),
+            PathPlannerError::Trajectory(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for PathPlannerError {
+    fn from(value: std::io::Error) -> Self {
+        PathPlannerError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for PathPlannerError {
+    fn from(value: serde_json::Error) -> Self {
+        PathPlannerError::Parse(value)
+    }
+}
+
+impl From<TrajectoryError> for PathPlannerError {
+    fn from(value: TrajectoryError) -> Self {
+        PathPlannerError::Trajectory(value)
+    }
+}
+
+/// A point on the spline, before time parameterization
+struct SplinePoint {
+    pos: f64,
+    point: Point,
+    /// Unit tangent
+    direction: Point,
+    max_velocity: f64,
+    max_acceleration: f64,
+}
+
+impl PathPlannerPath {
+    pub fn from_path(path: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(path)
+    }
+
+    fn segment_count(&self) -> usize {
+        self.waypoints.len().saturating_sub(1)
+    }
+
+    fn constraints_at(&self, pos: f64) -> &PathConstraints {
+        self.constraint_zones
+            .iter()
+            .find(|zone| zone.min_waypoint_relative_pos <= pos && pos <= zone.max_waypoint_relative_pos)
+            .map(|zone| &zone.constraints)
+            .unwrap_or(&self.global_constraints)
+    }
+
+    fn spline_points(&self) -> Vec<SplinePoint> {
+        let mut points = Vec::new();
+        for (i, (start, end)) in self.waypoints.iter().zip(self.waypoints.iter().skip(1)).enumerate() {
+            let p0 = start.anchor;
+            let p1 = start.next_control.unwrap_or(p0);
+            let p3 = end.anchor;
+            let p2 = end.prev_control.unwrap_or(p3);
+
+            // Include the end of the segment only for the last one, it's the start of the next
+            let steps = if i + 1 == self.segment_count() { SAMPLES_PER_SEGMENT + 1 } else { SAMPLES_PER_SEGMENT };
+            for step in 0..steps {
+                let t = step as f64 / SAMPLES_PER_SEGMENT as f64;
+                let pos = i as f64 + t;
+                let constraints = self.constraints_at(pos);
+
+                let point = bezier(p0, p1, p2, p3, t);
+                let d1 = bezier_derivative(p0, p1, p2, p3, t);
+                let d2 = bezier_second_derivative(p0, p1, p2, p3, t);
+                let speed = d1.x.hypot(d1.y);
+                let direction = if speed > 0. { Point { x: d1.x / speed, y: d1.y / speed } } else { Point { x: 0., y: 0. } };
+
+                // Limit centripetal acceleration to the max acceleration
+                let curvature = if speed > 0. { (d1.x * d2.y - d1.y * d2.x).abs() / speed.powi(3) } else { 0. };
+                let curvature_velocity = if curvature > 0. { (constraints.max_acceleration / curvature).sqrt() } else { f64::INFINITY };
+
+                points.push(SplinePoint {
+                    pos,
+                    point,
+                    direction,
+                    max_velocity: constraints.max_velocity.min(curvature_velocity),
+                    max_acceleration: constraints.max_acceleration,
+                });
+            }
+        }
+        points
+    }
+
+    /// Rotation targets, including the start and end state, as (waypoint relative pos, radians)
+    fn rotation_targets(&self) -> Vec<(f64, f64)> {
+        let mut targets: Vec<(f64, f64)> = self.rotation_targets
+            .iter()
+            .map(|target| (target.waypoint_relative_pos, target.rotation_degrees.to_radians()))
+            .collect();
+        let start = self.ideal_starting_state
+            .as_ref()
+            .map(|state| state.rotation.to_radians())
+            .or(targets.first().map(|target| target.1))
+            .unwrap_or(self.goal_end_state.rotation.to_radians());
+        targets.insert(0, (0., start));
+        targets.push((self.segment_count() as f64, self.goal_end_state.rotation.to_radians()));
+        targets.sort_by(|a, b| a.0.total_cmp(&b.0));
+        targets
+    }
+
+    fn heading_at(targets: &[(f64, f64)], pos: f64) -> f64 {
+        let next = targets.iter().position(|target| target.0 >= pos).unwrap_or(targets.len() - 1);
+        if next == 0 {
+            return targets[0].1;
+        }
+        let (start_pos, start) = targets[next - 1];
+        let (end_pos, end) = targets[next];
+        let progress = if end_pos > start_pos { (pos - start_pos) / (end_pos - start_pos) } else { 1. };
+
+        let (start, end) = (Angle::new::<radian>(start), Angle::new::<radian>(end));
+        normalize_angle(start + angle_difference(start, end) * progress).get::<radian>()
+    }
+
+    /// Generate a time-parameterized `Path`. Interior anchors become waypoints.
+    ///
+    /// The generated path always drives forwards with the heading following the rotation targets:
+    /// the file's `reversed` flag, `pointTowardsZones` and the angular limits
+    /// (`maxAngularVelocity`, `maxAngularAcceleration`) are not supported and are ignored
+    pub fn to_path(&self) -> Result<Path, TrajectoryError> {
+        let points = self.spline_points();
+        if points.is_empty() {
+            return Err(TrajectoryError::Empty);
+        }
+
+        let distances: Vec<f64> = points.windows(2)
+            .map(|pair| (pair[1].point.x - pair[0].point.x).hypot(pair[1].point.y - pair[0].point.y))
+            .collect();
+
+        // Forward pass limits acceleration, backward pass limits deceleration
+        let mut velocities: Vec<f64> = points.iter().map(|point| point.max_velocity).collect();
+        velocities[0] = velocities[0].min(self.ideal_starting_state.as_ref().map_or(0., |state| state.velocity));
+        for i in 1..points.len() {
+            let reachable = (velocities[i - 1].powi(2) + 2. * points[i - 1].max_acceleration * distances[i - 1]).sqrt();
+            velocities[i] = velocities[i].min(reachable);
+        }
+        let last = points.len() - 1;
+        velocities[last] = velocities[last].min(self.goal_end_state.velocity);
+        for i in (0..last).rev() {
+            let reachable = (velocities[i + 1].powi(2) + 2. * points[i].max_acceleration * distances[i]).sqrt();
+            velocities[i] = velocities[i].min(reachable);
+        }
+
+        let mut times = vec![0.];
+        for i in 0..last {
+            let average = (velocities[i] + velocities[i + 1]) / 2.;
+            let dt = if average > 0. { distances[i] / average } else { 0. };
+            times.push(times[i] + dt);
+        }
+
+        let targets = self.rotation_targets();
+        let headings: Vec<f64> = points.iter().map(|point| Self::heading_at(&targets, point.pos)).collect();
+
+        let mut samples: Vec<Sample> = Vec::new();
+        for (i, point) in points.iter().enumerate() {
+            // Skip zero-length steps, they would give duplicate timestamps
+            if i > 0 && times[i] <= times[i - 1] {
+                continue;
+            }
+            let prev = i.saturating_sub(1);
+            let next = (i + 1).min(last);
+            let dt = times[next] - times[prev];
+            let dheading = angle_difference(Angle::new::<radian>(headings[prev]), Angle::new::<radian>(headings[next])).get::<radian>();
+
+            samples.push(Sample {
+                t: times[i],
+                x: point.point.x,
+                y: point.point.y,
+                heading: headings[i],
+                velocity_x: velocities[i] * point.direction.x,
+                velocity_y: velocities[i] * point.direction.y,
+                angular_velocity: if dt > 0. { dheading / dt } else { 0. },
+                acceleration_x: None,
+                acceleration_y: None,
+                angular_acceleration: None,
+                module_forces_x: Vec::new(),
+                module_forces_y: Vec::new(),
+            });
+        }
+
+        let time_at = |pos: f64| {
+            let i = points.iter().position(|point| point.pos >= pos).unwrap_or(last);
+            times[i]
+        };
+        let waypoint_times: Vec<f64> = (0..self.waypoints.len()).map(|i| time_at(i as f64)).collect();
+        let split_waypoints = waypoint_times[1..waypoint_times.len() - 1].to_vec();
+
+        let mut path = Path::from_trajectory_data(TrajectoryData { samples, waypoints: split_waypoints })?;
+        path.waypoint_times = waypoint_times;
+        path.set_events(self.event_markers.iter().map(|marker| EventMarker {
+            name: marker.name.clone(),
+            timestamp: Time::new::<second>(time_at(marker.waypoint_relative_pos)),
+        }).collect());
+        Ok(path)
+    }
+}
+
+fn bezier(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point {
+    let u = 1. - t;
+    let (a, b, c, d) = (u * u * u, 3. * u * u * t, 3. * u * t * t, t * t * t);
+    Point {
+        x: a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+        y: a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+    }
+}
+
+fn bezier_derivative(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point {
+    let u = 1. - t;
+    let (a, b, c) = (3. * u * u, 6. * u * t, 3. * t * t);
+    Point {
+        x: a * (p1.x - p0.x) + b * (p2.x - p1.x) + c * (p3.x - p2.x),
+        y: a * (p1.y - p0.y) + b * (p2.y - p1.y) + c * (p3.y - p2.y),
+    }
+}
+
+fn bezier_second_derivative(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point {
+    let u = 1. - t;
+    Point {
+        x: 6. * u * (p2.x - 2. * p1.x + p0.x) + 6. * t * (p3.x - 2. * p2.x + p1.x),
+        y: 6. * u * (p2.y - 2. * p1.y + p0.y) + 6. * t * (p3.y - 2. * p2.y + p1.y),
+    }
+}
+
+/// A PathPlanner .auto file: a tree of commands, of which only the paths matter here
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PathPlannerAuto {
+    pub command: AutoCommand,
+    /// Paths are Choreo trajectories instead of PathPlanner paths
+    #[serde(default)]
+    pub choreo_auto: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", content = "data", rename_all = "lowercase")]
+pub enum AutoCommand {
+    Path {
+        #[serde(rename = "pathName")]
+        path_name: String,
+    },
+    Named {
+        name: Option<String>,
+    },
+    Wait {
+        #[serde(rename = "waitTime")]
+        wait_time: f64,
+    },
+    Sequential { commands: Vec<AutoCommand> },
+    Parallel { commands: Vec<AutoCommand> },
+    Race { commands: Vec<AutoCommand> },
+    Deadline { commands: Vec<AutoCommand> },
+}
+
+impl AutoCommand {
+    fn collect_paths(&self, names: &mut Vec<String>) {
+        match self {
+            AutoCommand::Path { path_name } => names.push(path_name.clone()),
+            AutoCommand::Sequential { commands }
+            | AutoCommand::Parallel { commands }
+            | AutoCommand::Race { commands }
+            | AutoCommand::Deadline { commands } => {
+                for command in commands {
+                    command.collect_paths(names);
+                }
+            }
+            AutoCommand::Named { .. } | AutoCommand::Wait { .. } => {}
+        }
+    }
+}
+
+impl PathPlannerAuto {
+    pub fn from_auto(auto: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(auto)
+    }
+
+    /// Names of the paths the auto runs, in order
+    pub fn path_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.command.collect_paths(&mut names);
+        names
+    }
+
+    /// Load the auto's paths in order from `directory`: `<name>.path` files, or `<name>.traj`
+    /// files for a Choreo auto
+    pub fn load_paths(&self, directory: impl AsRef<FsPath>) -> Result<Vec<(String, Path)>, PathPlannerError> {
+        let directory = directory.as_ref();
+        self.path_names()
+            .into_iter()
+            .map(|name| {
+                let path = if self.choreo_auto {
+                    Path::from_trajectory(&fs::read_to_string(directory.join(format!("{}.traj", name)))?)?
+                } else {
+                    PathPlannerPath::from_path(&fs::read_to_string(directory.join(format!("{}.path", name)))?)?.to_path()?
+                };
+                Ok((name, path))
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn straight_path() {
+    let data = r#"{
+        "version": "2025.0",
+        "waypoints": [
+            {"anchor": {"x": 1.0, "y": 1.0}, "prevControl": null, "nextControl": {"x": 2.0, "y": 1.0}, "isLocked": false, "linkedName": null},
+            {"anchor": {"x": 3.0, "y": 1.0}, "prevControl": {"x": 2.0, "y": 1.0}, "nextControl": {"x": 4.0, "y": 1.0}, "isLocked": false, "linkedName": null},
+            {"anchor": {"x": 5.0, "y": 1.0}, "prevControl": {"x": 4.0, "y": 1.0}, "nextControl": null, "isLocked": false, "linkedName": null}
+        ],
+        "rotationTargets": [],
+        "constraintZones": [],
+        "pointTowardsZones": [],
+        "eventMarkers": [{"name": "intake", "waypointRelativePos": 1.0, "endWaypointRelativePos": null, "command": null}],
+        "globalConstraints": {"maxVelocity": 2.0, "maxAcceleration": 2.0, "maxAngularVelocity": 540.0, "maxAngularAcceleration": 720.0, "nominalVoltage": 12.0, "unlimited": false},
+        "goalEndState": {"velocity": 0, "rotation": 90.0},
+        "reversed": false,
+        "folder": null,
+        "idealStartingState": {"velocity": 0, "rotation": 0.0},
+        "useDefaultConstraints": true
+    }"#;
+    let path = PathPlannerPath::from_path(data).unwrap().to_path().unwrap();
+
+    // 4 m at 2 m/s and 2 m/s^2: 1 s accelerating, 1 s cruising, 1 s braking
+    assert!((path.length().get::<second>() - 3.).abs() < 0.05);
+    assert_eq!(path.waypoints().len(), 1);
+    assert!((path.events()[0].timestamp.get::<second>() - 1.5).abs() < 0.05);
+
+    let end = path.get(path.length());
+    assert!((end.x.get::<uom::si::length::meter>() - 5.).abs() < 1e-9);
+    assert!((end.heading.get::<uom::si::angle::degree>() - 90.).abs() < 1e-9);
+}
+
+#[test]
+fn auto_path_order() {
+    let data = r#"{
+        "version": "2025.0",
+        "command": {"type": "sequential", "data": {"commands": [
+            {"type": "path", "data": {"pathName": "First"}},
+            {"type": "named", "data": {"name": "shoot"}},
+            {"type": "parallel", "data": {"commands": [
+                {"type": "path", "data": {"pathName": "Second"}},
+                {"type": "wait", "data": {"waitTime": 1.0}}
+            ]}}
+        ]}},
+        "resetOdom": true,
+        "folder": null,
+        "choreoAuto": false
+    }"#;
+    let auto = PathPlannerAuto::from_auto(data).unwrap();
+
+    assert_eq!(auto.path_names(), vec!["First", "Second"]);
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path as FsPath, PathBuf};
+use serde::{Serialize, Deserialize};
+use uom::si::f64::{Length, Mass, MomentOfInertia, Torque};
+use uom::si::{length::meter, mass::kilogram, moment_of_inertia::kilogram_square_meter, torque::newton_meter};
+use crate::{DriveType, Params, Path, TrajectoryError};
+use crate::geometry::Translation2d;
+use crate::expr::{Dimension, Expr, ExprError, Variables};
+
+/// A Choreo project (.chor): robot configuration, variables and generation settings shared by
+/// every trajectory in the project directory
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChoreoProject {
+    pub name: String,
+    pub version: String,
+    #[serde(rename = "type", default)]
+    pub drive_type: DriveType,
+    #[serde(default)]
+    pub variables: ProjectVariables,
+    pub config: RobotConfig,
+    #[serde(rename = "generationFeatures", default)]
+    pub generation_features: Vec<String>,
+    /// Directory the project was loaded from, used to find its .traj files
+    #[serde(skip)]
+    directory: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ProjectVariables {
+    #[serde(default)]
+    pub expressions: HashMap<String, VariableExpr>,
+    #[serde(default)]
+    pub poses: HashMap<String, PoseVariable>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VariableExpr {
+    pub dimension: String,
+    pub var: Expr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PoseVariable {
+    pub x: Expr,
+    pub y: Expr,
+    pub heading: Expr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModuleExpr {
+    pub x: Expr,
+    pub y: Expr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BumperExpr {
+    pub front: Expr,
+    pub side: Expr,
+    pub back: Expr,
+}
+
+/// Robot configuration as the project stores it: expressions, with motor limits before gearing
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RobotConfig {
+    pub front_left: ModuleExpr,
+    pub back_left: ModuleExpr,
+    pub mass: Expr,
+    pub inertia: Expr,
+    pub gearing: Expr,
+    pub radius: Expr,
+    /// Motor max velocity
+    pub vmax: Expr,
+    /// Motor max torque
+    pub tmax: Expr,
+    pub bumper: BumperExpr,
+    pub differential_track_width: Expr,
+}
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Expr(ExprError),
+    Trajectory(TrajectoryError),
+    /// The project wasn't read from a file, so there is no directory to find trajectories in
+    NoDirectory,
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::Io(err) => write!(f, "failed to read project: {}", err),
+            ProjectError::Parse(err) => write!(f, "failed to parse project: {}", err),
+            ProjectError::Expr(err) => write!(f, "failed to evaluate project expression: {}", err),
+            ProjectError::Trajectory(err) => write!(f, "failed to load trajectory: {}", err),
+            ProjectError::NoDirectory => write!(f, "project has no directory to load trajectories from"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProjectError::Io(err) => Some(err),
+            ProjectError::Parse(err) => Some(err),
+            ProjectError::Expr(err) => Some(err),
+            ProjectError::Trajectory(err) => Some(err),
+            ProjectError::NoDirectory => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProjectError {
+    fn from(value: std::io::Error) -> Self {
+        ProjectError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(value: serde_json::Error) -> Self {
+        ProjectError::Parse(value)
+    }
+}
+
+impl From<TrajectoryError> for ProjectError {
+    fn from(value: TrajectoryError) -> Self {
+        ProjectError::Trajectory(value)
+    }
+}
+
+impl From<ExprError> for ProjectError {
+    fn from(value: ExprError) -> Self {
+        ProjectError::Expr(value)
+    }
+}
+
+impl ChoreoProject {
+    pub fn from_project(project: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(project)
+    }
+
+    /// Read a .chor file. Its directory is remembered so the project's trajectories can be loaded
+    pub fn load(file: impl AsRef<FsPath>) -> Result<Self, ProjectError> {
+        let file = file.as_ref();
+        let mut project = Self::from_project(&fs::read_to_string(file)?)?;
+        project.directory = file.parent().map(FsPath::to_path_buf);
+        Ok(project)
+    }
+
+    /// Look for the project's trajectories in `directory`, for a project parsed with
+    /// `from_project`
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Project variables, for evaluating expressions in this project's trajectories
+    pub fn variables(&self) -> Variables {
+        let mut variables = Variables::default();
+        for (name, expr) in &self.variables.expressions {
+            variables.insert(name.clone(), expr.var.clone());
+        }
+        for (name, pose) in &self.variables.poses {
+            variables.insert(format!("{}.x", name), pose.x.clone());
+            variables.insert(format!("{}.y", name), pose.y.clone());
+            variables.insert(format!("{}.heading", name), pose.heading.clone());
+        }
+        variables
+    }
+
+    /// Evaluate the robot configuration. Module positions are front left, front right, back left,
+    /// back right for swerve and left, right for differential drive
+    pub fn params(&self) -> Result<Params, ExprError> {
+        let variables = self.variables();
+        let config = &self.config;
+        let value = |expr: &Expr, dimension| expr.evaluate_as(&variables, dimension);
+
+        let length = |expr: &Expr| expr.length(&variables);
+
+        let gearing = value(&config.gearing, Dimension::NONE)?;
+        let modules = match self.drive_type {
+            DriveType::Swerve => {
+                let front = Translation2d::new(length(&config.front_left.x)?, length(&config.front_left.y)?);
+                let back = Translation2d::new(length(&config.back_left.x)?, length(&config.back_left.y)?);
+                vec![front, Translation2d::new(front.x, -front.y), back, Translation2d::new(back.x, -back.y)]
+            }
+            DriveType::Differential => {
+                let half_width = length(&config.differential_track_width)? / 2.;
+                let zero = Length::new::<meter>(0.);
+                vec![Translation2d::new(zero, half_width), Translation2d::new(zero, -half_width)]
+            }
+        };
+
+        Ok(Params {
+            mass: Mass::new::<kilogram>(value(&config.mass, Dimension::MASS)?),
+            inertia: MomentOfInertia::new::<kilogram_square_meter>(value(&config.inertia, Dimension { length: 2, mass: 1, ..Dimension::NONE })?),
+            wheel_radius: length(&config.radius)?,
+            max_wheel_velocity: config.vmax.angular_velocity(&variables)? / gearing,
+            max_wheel_torque: Torque::new::<newton_meter>(value(&config.tmax, Dimension { length: 2, time: -2, angle: 0, mass: 1 })? * gearing),
+            bumper_length: length(&config.bumper.front)? + length(&config.bumper.back)?,
+            bumper_width: length(&config.bumper.side)? * 2.,
+            modules,
+            drive_type: self.drive_type,
+        })
+    }
+
+    /// Names of the .traj files next to the project file
+    pub fn path_names(&self) -> Result<Vec<String>, ProjectError> {
+        let directory = self.directory.as_ref().ok_or(ProjectError::NoDirectory)?;
+        let mut names = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let file = entry?.path();
+            if file.extension().is_some_and(|extension| extension == "traj") {
+                if let Some(name) = file.file_stem().and_then(|name| name.to_str()) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Load one of the project's trajectories by name, with the project's robot configuration and
+    /// its expressions evaluated against the project's variables
+    pub fn load_path(&self, name: &str) -> Result<Path, ProjectError> {
+        let directory = self.directory.as_ref().ok_or(ProjectError::NoDirectory)?;
+        let data = fs::read_to_string(directory.join(format!("{}.traj", name)))?;
+        let mut path = Path::from_trajectory_with(&data, &self.variables())?;
+        path.params = Some(self.params()?);
+        Ok(path)
+    }
+
+    /// Load every trajectory in the project, keyed by name
+    pub fn load_paths(&self) -> Result<HashMap<String, Path>, ProjectError> {
+        self.path_names()?
+            .into_iter()
+            .map(|name| Ok((name.clone(), self.load_path(&name)?)))
+            .collect()
+    }
+}
+
+#[test]
+fn project_params() {
+    let data = r#"{
+        "name": "Robot",
+        "version": "v2025.0.0",
+        "type": "Swerve",
+        "variables": {
+            "expressions": {"wheelbase": {"dimension": "Length", "var": {"exp": "22 in", "val": 0.5588}}},
+            "poses": {"start": {"x": {"exp": "1 m", "val": 1}, "y": {"exp": "2 m", "val": 2}, "heading": {"exp": "0 rad", "val": 0}}}
+        },
+        "config": {
+            "frontLeft": {"x": {"exp": "wheelbase / 2", "val": 0.2794}, "y": {"exp": "11 in", "val": 0.2794}},
+            "backLeft": {"x": {"exp": "-wheelbase / 2", "val": -0.2794}, "y": {"exp": "11 in", "val": 0.2794}},
+            "mass": {"exp": "60 kg", "val": 60},
+            "inertia": {"exp": "6 kg m ^ 2", "val": 6},
+            "gearing": {"exp": "6.5", "val": 6.5},
+            "radius": {"exp": "2 in", "val": 0.0508},
+            "vmax": {"exp": "6000 RPM", "val": 628.3185307179587},
+            "tmax": {"exp": "1.2 N * m", "val": 1.2},
+            "bumper": {"front": {"exp": "16 in", "val": 0.4064}, "side": {"exp": "16 in", "val": 0.4064}, "back": {"exp": "16 in", "val": 0.4064}},
+            "differentialTrackWidth": {"exp": "22 in", "val": 0.5588}
+        },
+        "generationFeatures": []
+    }"#;
+    let project = ChoreoProject::from_project(data).unwrap();
+    let params = project.params().unwrap();
+
+    assert_eq!(params.modules.len(), 4);
+    assert!((params.modules[0].x.get::<meter>() - 0.2794).abs() < 1e-9);
+    assert!((params.modules[3].y.get::<meter>() + 0.2794).abs() < 1e-9);
+    assert!((params.max_wheel_velocity.get::<uom::si::angular_velocity::radian_per_second>() - 628.3185307179587 / 6.5).abs() < 1e-9);
+    assert!((params.bumper_length.get::<meter>() - 0.8128).abs() < 1e-9);
+    assert!(project.variables().get("start.x").is_some());
+
+    assert!(matches!(project.path_names(), Err(ProjectError::NoDirectory)));
+    assert!(matches!(project.load_path("Test"), Err(ProjectError::NoDirectory)));
+}
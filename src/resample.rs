@@ -0,0 +1,117 @@
+use uom::si::f64::Time;
+use uom::si::time::second;
+use crate::{Path, Pose};
+
+/// Timestamps closer than this are treated as the same step
+const EPSILON: f64 = 1e-9;
+
+/// Iterator over a path at a fixed timestep, from `Path::iter_at`
+pub struct Resample<'a> {
+    path: &'a Path,
+    dt: f64,
+    align_to_waypoints: bool,
+    include_end: bool,
+    /// Start of every aligned section, then the end of the path. Built on the first `next`
+    boundaries: Option<Vec<f64>>,
+    section: usize,
+    step: usize,
+    last: Option<f64>,
+}
+
+impl Path {
+    /// Iterate over the path every `dt`, starting at the first sample, using the same
+    /// interpolation as `get`. Yields nothing if `dt` isn't positive. An infinite `dt` yields
+    /// only the start of the path, and of each section when aligned to waypoints
+    pub fn iter_at(&self, dt: Time) -> Resample<'_> {
+        Resample {
+            path: self,
+            dt: dt.get::<second>(),
+            align_to_waypoints: false,
+            include_end: false,
+            boundaries: None,
+            section: 0,
+            step: 0,
+            last: None,
+        }
+    }
+}
+
+impl Resample<'_> {
+    /// Restart the timestep at every split waypoint, so each waypoint is yielded exactly
+    pub fn aligned_to_waypoints(mut self) -> Self {
+        self.align_to_waypoints = true;
+        self
+    }
+
+    /// Always yield the last sample, even if it isn't a whole number of steps from the start
+    pub fn including_end(mut self) -> Self {
+        self.include_end = true;
+        self
+    }
+
+    fn section_boundaries(&self) -> Vec<f64> {
+        let start = **self.path.samples.first_key_value().unwrap().0;
+        let end = self.path.length().get::<second>();
+        let mut boundaries = vec![start];
+        if self.align_to_waypoints {
+            boundaries.extend(self.path.waypoints.iter().filter(|&&t| start + EPSILON < t && t < end - EPSILON));
+        }
+        boundaries.push(end);
+        boundaries
+    }
+
+    fn next_time(&mut self) -> Option<f64> {
+        if self.dt.is_nan() || self.dt <= 0. {
+            return None;
+        }
+        if self.boundaries.is_none() {
+            self.boundaries = Some(self.section_boundaries());
+        }
+        let boundaries = self.boundaries.as_ref().unwrap();
+        let end = *boundaries.last().unwrap();
+
+        while self.section + 1 < boundaries.len() {
+            let (from, to) = (boundaries[self.section], boundaries[self.section + 1]);
+            // Step 0 is exactly `from`, since `0 * dt` is NaN for an infinite `dt`
+            let t = if self.step == 0 { from } else { from + self.step as f64 * self.dt };
+            let is_last = self.section + 2 == boundaries.len();
+            // The end of a section is the start of the next one, except for the last section
+            if t < to - EPSILON || (is_last && t <= to + EPSILON) {
+                self.step += 1;
+                return Some(t);
+            }
+            self.section += 1;
+            self.step = 0;
+        }
+
+        match self.last {
+            Some(last) if self.include_end && last < end - EPSILON => Some(end),
+            _ => None,
+        }
+    }
+}
+
+impl Iterator for Resample<'_> {
+    type Item = (Time, Pose);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.next_time()?;
+        self.last = Some(t);
+        let t = Time::new::<second>(t);
+        Some((t, self.path.get(t)))
+    }
+}
+
+#[test]
+fn resample() {
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let times = |resample: Resample| resample.map(|(t, _)| t.get::<second>()).collect::<Vec<_>>();
+
+    assert_eq!(times(path.iter_at(Time::new::<second>(0.5))), vec![0., 0.5, 1., 1.5, 2.]);
+    assert_eq!(times(path.iter_at(Time::new::<second>(0.75))), vec![0., 0.75, 1.5]);
+    assert_eq!(times(path.iter_at(Time::new::<second>(0.75)).including_end()), vec![0., 0.75, 1.5, 2.]);
+    assert_eq!(times(path.iter_at(Time::new::<second>(0.75)).aligned_to_waypoints()), vec![0., 0.75, 1., 1.75]);
+    assert!(times(path.iter_at(Time::new::<second>(0.))).is_empty());
+    assert_eq!(times(path.iter_at(Time::new::<second>(f64::INFINITY))), vec![0.]);
+    assert_eq!(times(path.iter_at(Time::new::<second>(f64::INFINITY)).aligned_to_waypoints().including_end()), vec![0., 1., 2.]);
+}
@@ -0,0 +1,177 @@
+use uom::si::f64::{Angle, Length, Time};
+use uom::si::{length::meter, velocity::meter_per_second, time::second};
+use ordered_float::NotNan;
+use crate::{angle_difference, Path, Pose};
+use crate::geometry::Rotation2d;
+
+/// The point on a path nearest to a measured pose
+#[derive(Clone, Debug)]
+pub struct ClosestPoint {
+    pub time: Time,
+    /// Distance travelled along the path to this point
+    pub distance: Length,
+    pub pose: Pose,
+    /// Straight-line distance from the measured pose to the path
+    pub offset: Length,
+}
+
+/// Tracking error in the path's own frame. Positive along-track is ahead of the setpoint and
+/// positive cross-track is to the left of the direction of travel
+#[derive(Clone, Debug)]
+pub struct TrackingError {
+    pub along_track: Length,
+    pub cross_track: Length,
+    /// Measured heading minus setpoint heading, the short way around
+    pub heading: Angle,
+    /// Signed distance from the nearest point on the path, regardless of time
+    pub lateral_deviation: Length,
+}
+
+/// Direction of travel, or the heading when standing still
+fn tangent(pose: &Pose) -> Rotation2d {
+    let (vx, vy) = (pose.velocity_x.get::<meter_per_second>(), pose.velocity_y.get::<meter_per_second>());
+    if vx.hypot(vy) > 1e-6 {
+        Rotation2d::from_components(vx, vy)
+    } else {
+        pose.rotation()
+    }
+}
+
+/// (along, cross) components of `measured - reference` in the reference's tangent frame
+fn decompose(reference: &Pose, measured: &Pose) -> (f64, f64) {
+    let error = (measured.translation() - reference.translation()).rotate_by(tangent(reference).inverse());
+    (error.x.get::<meter>(), error.y.get::<meter>())
+}
+
+impl Path {
+    /// Error of `measured` against the setpoint at `elapsed`, in the path's frame
+    pub fn tracking_error(&self, measured: &Pose, elapsed: Time) -> TrackingError {
+        let setpoint = self.get(elapsed);
+        let (along_track, cross_track) = decompose(&setpoint, measured);
+
+        let closest = self.closest(measured);
+        let (_, side) = decompose(&closest.pose, measured);
+        let lateral_deviation = if side < 0. { -closest.offset } else { closest.offset };
+
+        TrackingError {
+            along_track: Length::new::<meter>(along_track),
+            cross_track: Length::new::<meter>(cross_track),
+            heading: angle_difference(setpoint.heading, measured.heading),
+            lateral_deviation,
+        }
+    }
+
+    /// The nearest point on the path to `measured`, searching every segment between samples
+    pub fn closest(&self, measured: &Pose) -> ClosestPoint {
+        self.closest_between(measured, self.samples.iter(), f64::NEG_INFINITY, f64::INFINITY)
+    }
+
+    /// Like `closest`, but only searching within `window` either side of `hint`. Useful to stay on
+    /// the right pass of a path that crosses itself. Only the segments overlapping the window are
+    /// projected onto
+    pub fn closest_near(&self, measured: &Pose, hint: Time, window: Time) -> ClosestPoint {
+        let (hint, window) = (hint.get::<second>(), window.get::<second>().abs());
+        let (Ok(from), Ok(to)) = (NotNan::new(hint - window), NotNan::new(hint + window)) else {
+            return self.closest(measured);
+        };
+        // The samples in the window and one either side, for the segments crossing its edges
+        let start = self.samples.range(..from).next_back().map_or(from, |(t, _)| *t);
+        let end = self.samples.range(to..).next().map_or(to, |(t, _)| *t);
+        self.closest_between(measured, self.samples.range(start..=end), *from, *to)
+    }
+
+    fn closest_between<'a>(
+        &self,
+        measured: &Pose,
+        samples: impl Iterator<Item = (&'a NotNan<f64>, &'a Pose)>,
+        from: f64,
+        to: f64,
+    ) -> ClosestPoint {
+        let (px, py) = (measured.x.get::<meter>(), measured.y.get::<meter>());
+        let samples: Vec<(f64, f64, f64)> = samples
+            .map(|(t, pose)| (**t, pose.x.get::<meter>(), pose.y.get::<meter>()))
+            .collect();
+
+        // (time, distance along path, offset)
+        let mut best: Option<(f64, f64, f64)> = None;
+        let start = self.distance_at(Time::new::<second>(samples[0].0)).get::<meter>();
+        let mut travelled = start;
+        let mut consider = |time: f64, distance: f64, offset: f64| {
+            if best.is_none_or(|(_, _, best_offset)| offset < best_offset) {
+                best = Some((time, distance, offset));
+            }
+        };
+
+        for pair in samples.windows(2) {
+            let ((t0, x0, y0), (t1, x1, y1)) = (pair[0], pair[1]);
+            let (dx, dy) = (x1 - x0, y1 - y0);
+            let length = dx.hypot(dy);
+
+            if t1 >= from && t0 <= to {
+                // Project onto the segment, then clamp to the part inside the window
+                let projected = if length > 0. { ((px - x0) * dx + (py - y0) * dy) / (length * length) } else { 0. };
+                let low = ((from - t0) / (t1 - t0)).max(0.);
+                let high = ((to - t0) / (t1 - t0)).min(1.);
+                let u = projected.clamp(low, high);
+
+                let offset = (x0 + dx * u - px).hypot(y0 + dy * u - py);
+                consider(t0 + (t1 - t0) * u, travelled + length * u, offset);
+            }
+            travelled += length;
+        }
+
+        // A single sample, or a window past the ends of the path
+        let (time, distance, offset) = best.unwrap_or_else(|| {
+            let (t, x, y) = if to < samples[0].0 { samples[0] } else { samples[samples.len() - 1] };
+            let distance = if to < samples[0].0 { start } else { travelled };
+            (t, distance, (x - px).hypot(y - py))
+        });
+
+        ClosestPoint {
+            time: Time::new::<second>(time),
+            distance: Length::new::<meter>(distance),
+            pose: self.get(Time::new::<second>(time)),
+            offset: Length::new::<meter>(offset),
+        }
+    }
+}
+
+#[test]
+fn closest_point() {
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let mut measured = path.get(Time::new::<second>(0.));
+    measured.x = Length::new::<meter>(2.5);
+    measured.y = Length::new::<meter>(1.5);
+
+    let closest = path.closest(&measured);
+    assert!((closest.time.get::<second>() - 1.5).abs() < 1e-9);
+    assert!((closest.distance.get::<meter>() - 1.5).abs() < 1e-9);
+    assert!((closest.offset.get::<meter>() - 0.5).abs() < 1e-9);
+
+    let near = path.closest_near(&measured, Time::new::<second>(0.5), Time::new::<second>(0.25));
+    assert!((near.time.get::<second>() - 0.75).abs() < 1e-9);
+
+    let near = path.closest_near(&measured, Time::new::<second>(1.6), Time::new::<second>(0.2));
+    assert!((near.time.get::<second>() - 1.5).abs() < 1e-9);
+    assert!((near.distance.get::<meter>() - 1.5).abs() < 1e-9);
+    let past_end = path.closest_near(&measured, Time::new::<second>(5.), Time::new::<second>(1.));
+    assert_eq!(past_end.time.get::<second>(), 2.);
+    assert!((past_end.distance.get::<meter>() - 2.).abs() < 1e-9);
+}
+
+#[test]
+fn tracking_error() {
+    use uom::si::angle::radian;
+
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let mut measured = path.get(Time::new::<second>(1.));
+    measured.x = Length::new::<meter>(1.8);
+    measured.y = Length::new::<meter>(0.7);
+    measured.heading = Angle::new::<radian>(0.1);
+
+    let error = path.tracking_error(&measured, Time::new::<second>(1.));
+    assert!((error.along_track.get::<meter>() + 0.2).abs() < 1e-9);
+    assert!((error.cross_track.get::<meter>() + 0.3).abs() < 1e-9);
+    assert!((error.heading.get::<radian>() - 0.1).abs() < 1e-9);
+    assert!((error.lateral_deviation.get::<meter>() + 0.3).abs() < 1e-9);
+}
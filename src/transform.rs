@@ -0,0 +1,248 @@
+use std::f64::consts::PI;
+use ordered_float::NotNan;
+use uom::si::f64::{Angle, Length, Time};
+use uom::si::{angle::radian, time::second};
+use crate::{normalize_angle, Constraint, ConstraintScope, EventMarker, Field, Path, Pose, Symmetry};
+use crate::geometry::{Pose2d, Transform2d, Translation2d};
+
+impl Path {
+    /// A copy of the path with every timestamp `t` moved to `offset + t * scale` and every pose
+    /// mapped through `pose`. Waypoints and events keep their order by time
+    fn retimed(&self, scale: f64, offset: f64, pose: impl Fn(&Pose) -> Pose) -> Path {
+        let time = |t: f64| offset + t * scale;
+        let mut path = self.clone();
+        // A finite scale and offset can't turn a sample time into NaN
+        path.samples = self.samples.iter()
+            .filter_map(|(t, sample)| Some((NotNan::new(time(**t)).ok()?, pose(sample))))
+            .collect();
+        path.waypoints = self.waypoints.iter().map(|&t| time(t)).collect();
+        path.waypoints.sort_by(f64::total_cmp);
+        path.waypoint_times = self.waypoint_times.iter().map(|&t| time(t)).collect();
+        path.waypoint_times.sort_by(f64::total_cmp);
+        path.set_events(self.events.iter().map(|event| EventMarker {
+            name: event.name.clone(),
+            timestamp: Time::new::<second>(time(event.timestamp.get::<second>())),
+        }).collect());
+        path
+    }
+
+    /// The same path taking `factor` times as long, so 1.25 runs it at 80% speed. Velocities are
+    /// divided by `factor` and accelerations by its square, so feedforward stays consistent.
+    /// Waypoints and events are remapped to the new timestamps. None if `factor` isn't positive
+    /// and finite
+    pub fn time_scaled(&self, factor: f64) -> Option<Path> {
+        if !(factor.is_finite() && factor > 0.) {
+            return None;
+        }
+        let start = **self.samples.first_key_value()?.0;
+        Some(self.retimed(factor, start - start * factor, |pose| Pose {
+            velocity_x: pose.velocity_x / factor,
+            velocity_y: pose.velocity_y / factor,
+            angular_velocity: pose.angular_velocity / factor,
+            acceleration_x: pose.acceleration_x / (factor * factor),
+            acceleration_y: pose.acceleration_y / (factor * factor),
+            angular_acceleration: pose.angular_acceleration / (factor * factor),
+            ..pose.clone()
+        }))
+    }
+
+    /// The same path driven end to start, starting at zero. Velocities are negated while
+    /// accelerations, which are even in time, are kept. Waypoints, events and constraint scopes
+    /// are remapped to match
+    pub fn reversed(&self) -> Path {
+        let end = **self.samples.last_key_value().unwrap().0;
+        let mut path = self.retimed(-1., end, |pose| Pose {
+            velocity_x: -pose.velocity_x,
+            velocity_y: -pose.velocity_y,
+            angular_velocity: -pose.angular_velocity,
+            ..pose.clone()
+        });
+
+        let count = self.waypoint_times.len();
+        for constraint in &mut path.constraints {
+            let scope = constraint.scope_mut();
+            *scope = match *scope {
+                ConstraintScope::Waypoint(waypoint) => ConstraintScope::Waypoint(waypoint.reversed(count)),
+                ConstraintScope::Segment(from, to) => ConstraintScope::Segment(to.reversed(count), from.reversed(count)),
+            };
+            if let Constraint::WptVelocityDirection { direction, .. } = constraint {
+                *direction = normalize_angle(*direction + Angle::new::<radian>(PI));
+            }
+        }
+        path.reverse_source_waypoints();
+        path
+    }
+
+    /// The path for the other alliance, using the field's symmetry
+    pub fn mirror(&self, field: &Field) -> Path {
+        self.flipped(field, field.symmetry)
+    }
+
+    /// The path for the other alliance on `field`. Samples are flipped with `Pose::flipped`, and
+    /// constraint positions and directions along with them. Timing is unchanged
+    pub fn flipped(&self, field: &Field, symmetry: Symmetry) -> Path {
+        let mut path = self.clone();
+        path.samples = self.samples.iter().map(|(t, pose)| (*t, pose.flipped(field, symmetry))).collect();
+
+        let flip_x = |x: Length| field.length - x;
+        let flip_y = |y: Length| match symmetry {
+            Symmetry::Rotational => field.width - y,
+            Symmetry::Mirrored => y,
+        };
+        for constraint in &mut path.constraints {
+            match constraint {
+                Constraint::WptVelocityDirection { direction, .. } => *direction = match symmetry {
+                    Symmetry::Rotational => normalize_angle(*direction + Angle::new::<radian>(PI)),
+                    Symmetry::Mirrored => normalize_angle(Angle::new::<radian>(PI) - *direction),
+                },
+                Constraint::PointAt { x, y, .. }
+                | Constraint::KeepInCircle { x, y, .. }
+                | Constraint::KeepOutCircle { x, y, .. } => (*x, *y) = (flip_x(*x), flip_y(*y)),
+                // Keep the corner at the low x and y, with the same size
+                Constraint::KeepInRectangle { x, y, w, h, .. } => {
+                    *x = flip_x(*x + *w);
+                    if symmetry == Symmetry::Rotational {
+                        *y = flip_y(*y + *h);
+                    }
+                }
+                _ => {}
+            }
+        }
+        path.map_source_waypoints(|pose| pose.flipped(field, symmetry));
+        path
+    }
+
+    /// The path moved rigidly by `transform`, as if it was authored in a frame placed at
+    /// `transform` on the field. Positions and headings are moved, velocities and accelerations
+    /// rotated. Circle and point constraints move with the path, and rotated keep-in rectangles
+    /// become their bounding box
+    pub fn transformed(&self, transform: &Transform2d) -> Path {
+        let place = |x: Length, y: Length| {
+            let placed = Translation2d::new(x, y).rotate_by(transform.rotation) + transform.translation;
+            (placed.x, placed.y)
+        };
+        let turn = transform.rotation.angle();
+
+        let mut path = self.clone();
+        path.samples = self.samples.iter().map(|(t, pose)| {
+            let (x, y) = place(pose.x, pose.y);
+            let (velocity_x, velocity_y) = transform.rotation.rotate_vector(pose.velocity_x, pose.velocity_y);
+            let (acceleration_x, acceleration_y) = transform.rotation.rotate_vector(pose.acceleration_x, pose.acceleration_y);
+            (*t, Pose { x, y, heading: pose.heading + turn, velocity_x, velocity_y, acceleration_x, acceleration_y, ..pose.clone() })
+        }).collect();
+
+        for constraint in &mut path.constraints {
+            match constraint {
+                Constraint::WptVelocityDirection { direction, .. } => *direction = normalize_angle(*direction + turn),
+                Constraint::PointAt { x, y, .. }
+                | Constraint::KeepInCircle { x, y, .. }
+                | Constraint::KeepOutCircle { x, y, .. } => (*x, *y) = place(*x, *y),
+                Constraint::KeepInRectangle { x, y, w, h, .. } => {
+                    let corners = [place(*x, *y), place(*x + *w, *y), place(*x, *y + *h), place(*x + *w, *y + *h)];
+                    let (min_x, max_x) = corners.iter().fold((corners[0].0, corners[0].0), |(min, max), c| (min.min(c.0), max.max(c.0)));
+                    let (min_y, max_y) = corners.iter().fold((corners[0].1, corners[0].1), |(min, max), c| (min.min(c.1), max.max(c.1)));
+                    (*x, *y, *w, *h) = (min_x, min_y, max_x - min_x, max_y - min_y);
+                }
+                _ => {}
+            }
+        }
+        path.map_source_waypoints(|pose| {
+            let (x, y) = place(pose.x, pose.y);
+            Pose { x, y, heading: pose.heading + turn, ..pose.clone() }
+        });
+        path
+    }
+
+    /// The path as seen from `pose`, like `Pose2d::relative_to`. To start a path from the robot's
+    /// pose at enable, use `path.relative_to(&start).transformed(&(actual - Pose2d::identity()))`
+    pub fn relative_to(&self, pose: &Pose2d) -> Path {
+        self.transformed(&Transform2d::between(pose, &Pose2d::identity()))
+    }
+}
+
+#[test]
+fn time_scaled() {
+    use uom::si::{length::meter, velocity::meter_per_second};
+
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let slow = path.time_scaled(2.).unwrap();
+
+    assert_eq!(slow.length().get::<second>(), 4.);
+    assert_eq!(slow.waypoints(), &[2.]);
+    assert_eq!(slow.events()[0].timestamp.get::<second>(), 3.);
+
+    let pose = slow.get(Time::new::<second>(2.));
+    assert_eq!(pose.x.get::<meter>(), 2.);
+    assert_eq!(pose.velocity_x.get::<meter_per_second>(), 0.5);
+
+    assert!(path.time_scaled(0.).is_none());
+    assert!(path.time_scaled(f64::NAN).is_none());
+}
+
+#[test]
+fn reversed() {
+    use uom::si::{length::meter, velocity::meter_per_second};
+    use crate::WaypointId;
+
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let reversed = path.reversed();
+
+    assert_eq!(reversed.length().get::<second>(), 2.);
+    assert_eq!(reversed.waypoints(), &[1.]);
+    assert_eq!(reversed.events()[0].timestamp.get::<second>(), 0.5);
+    assert_eq!(reversed.constraints()[0].scope(), ConstraintScope::Waypoint(WaypointId::Last));
+
+    let pose = reversed.get(Time::new::<second>(0.5));
+    assert_eq!(pose.x.get::<meter>(), 2.5);
+    assert_eq!(reversed.get(Time::new::<second>(1.)).velocity_x.get::<meter_per_second>(), -1.);
+    assert_eq!(reversed.reversed().get(Time::new::<second>(0.5)).x, path.get(Time::new::<second>(0.5)).x);
+}
+
+#[test]
+fn flipped() {
+    use uom::si::f64::{AngularVelocity, Velocity};
+    use uom::si::{length::meter, velocity::meter_per_second, angular_velocity::radian_per_second};
+
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let field = Field::new(Length::new::<meter>(16.), Length::new::<meter>(8.));
+    let t = Time::new::<second>(1.);
+
+    let rotated = path.flipped(&field, Symmetry::Rotational).get(t);
+    assert_eq!((rotated.x.get::<meter>(), rotated.y.get::<meter>()), (14., 7.));
+    assert_eq!(rotated.velocity_x.get::<meter_per_second>(), -1.);
+    assert_eq!(rotated.heading.get::<radian>().abs(), PI);
+
+    let mirrored = path.flipped(&field, Symmetry::Mirrored).get(t);
+    assert_eq!((mirrored.x.get::<meter>(), mirrored.y.get::<meter>()), (14., 1.));
+    assert_eq!(mirrored.velocity_x.get::<meter_per_second>(), -1.);
+
+    let mut spinning = path.get(t);
+    spinning.heading = Angle::new::<radian>(0.5);
+    spinning.velocity_y = Velocity::new::<meter_per_second>(1.);
+    spinning.angular_velocity = AngularVelocity::new::<radian_per_second>(1.);
+    let rotated = spinning.flipped(&field, Symmetry::Rotational);
+    let mirrored = spinning.flipped(&field, Symmetry::Mirrored);
+    assert_eq!((rotated.velocity_y, rotated.angular_velocity), (-spinning.velocity_y, spinning.angular_velocity));
+    assert_eq!((mirrored.velocity_y, mirrored.angular_velocity), (spinning.velocity_y, -spinning.angular_velocity));
+    assert!((mirrored.heading.get::<radian>() - (PI - 0.5)).abs() < 1e-12);
+}
+
+#[test]
+fn transformed() {
+    use uom::si::{length::meter, velocity::meter_per_second};
+    use crate::geometry::Rotation2d;
+
+    let path = Path::from_trajectory(crate::TEST_TRAJ_2025).unwrap();
+    let m = Length::new::<meter>;
+    let transform = Transform2d::new(Translation2d::new(m(1.), m(0.)), Rotation2d::new(Angle::new::<radian>(PI / 2.)));
+
+    let pose = path.transformed(&transform).get(Time::new::<second>(1.));
+    assert!((pose.x.get::<meter>() - 0.).abs() < 1e-9 && (pose.y.get::<meter>() - 2.).abs() < 1e-9);
+    assert!(pose.velocity_x.get::<meter_per_second>().abs() < 1e-9);
+    assert!((pose.velocity_y.get::<meter_per_second>() - 1.).abs() < 1e-9);
+    assert!((pose.heading.get::<radian>() - PI / 2.).abs() < 1e-9);
+
+    let start = path.get(Time::new::<second>(0.)).pose2d();
+    let local = path.relative_to(&start).get(Time::new::<second>(1.));
+    assert!((local.x.get::<meter>() - 1.).abs() < 1e-9 && local.y.get::<meter>().abs() < 1e-9);
+}
@@ -0,0 +1,121 @@
+use serde::{Serialize, Deserialize};
+use uom::si::{length::meter, angle::radian, velocity::meter_per_second,
+              angular_velocity::radian_per_second, acceleration::meter_per_second_squared};
+use crate::{Path, Sample, TrajectoryData, TrajectoryError};
+
+/// One state of a WPILib `Trajectory`, as written by `TrajectoryUtil` and PathWeaver
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WpilibState {
+    /// s
+    pub time: f64,
+    /// m/s, along the heading
+    pub velocity: f64,
+    /// m/s^2
+    pub acceleration: f64,
+    pub pose: WpilibPose,
+    /// rad/m
+    pub curvature: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WpilibPose {
+    pub translation: WpilibTranslation,
+    pub rotation: WpilibRotation,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WpilibTranslation {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WpilibRotation {
+    pub radians: f64,
+}
+
+impl Path {
+    /// Load a WPILib trajectory JSON array. WPILib states describe a robot driving along its
+    /// heading, so field velocity points along the heading
+    pub fn from_wpilib(trajectory: &str) -> Result<Self, TrajectoryError> {
+        let states = serde_json::from_str::<Vec<WpilibState>>(trajectory)?;
+        let samples = states.iter().map(|state| {
+            let heading = state.pose.rotation.radians;
+            let angular_velocity = state.velocity * state.curvature;
+            Sample {
+                t: state.time,
+                x: state.pose.translation.x,
+                y: state.pose.translation.y,
+                heading,
+                velocity_x: state.velocity * heading.cos(),
+                velocity_y: state.velocity * heading.sin(),
+                angular_velocity,
+                // Tangential plus centripetal
+                acceleration_x: Some(state.acceleration * heading.cos() - state.velocity * angular_velocity * heading.sin()),
+                acceleration_y: Some(state.acceleration * heading.sin() + state.velocity * angular_velocity * heading.cos()),
+                angular_acceleration: None,
+                module_forces_x: Vec::new(),
+                module_forces_y: Vec::new(),
+            }
+        }).collect();
+
+        Self::from_trajectory_data(TrajectoryData { samples, waypoints: Vec::new() })
+    }
+
+    /// The path's samples as WPILib states. Velocity and acceleration are the components along
+    /// the heading
+    pub fn to_wpilib_states(&self) -> Vec<WpilibState> {
+        self.samples.iter().map(|(t, pose)| {
+            let heading = pose.heading.get::<radian>();
+            let velocity = pose.velocity_x.get::<meter_per_second>() * heading.cos()
+                + pose.velocity_y.get::<meter_per_second>() * heading.sin();
+            let acceleration = pose.acceleration_x.get::<meter_per_second_squared>() * heading.cos()
+                + pose.acceleration_y.get::<meter_per_second_squared>() * heading.sin();
+            let curvature = if velocity.abs() > f64::EPSILON {
+                pose.angular_velocity.get::<radian_per_second>() / velocity
+            } else {
+                0.
+            };
+
+            WpilibState {
+                time: **t,
+                velocity,
+                acceleration,
+                pose: WpilibPose {
+                    translation: WpilibTranslation { x: pose.x.get::<meter>(), y: pose.y.get::<meter>() },
+                    rotation: WpilibRotation { radians: pose.heading.get::<radian>() },
+                },
+                curvature,
+            }
+        }).collect()
+    }
+
+    /// Serialize the path as a WPILib trajectory JSON array
+    pub fn to_wpilib(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&self.to_wpilib_states())
+    }
+}
+
+#[test]
+fn wpilib_round_trip() {
+    let data = r#"[
+        {"time": 0.0, "velocity": 0.0, "acceleration": 1.0, "pose": {"translation": {"x": 0.0, "y": 0.0}, "rotation": {"radians": 0.0}}, "curvature": 0.0},
+        {"time": 1.0, "velocity": 1.0, "acceleration": 0.0, "pose": {"translation": {"x": 0.5, "y": 0.0}, "rotation": {"radians": 0.0}}, "curvature": 0.5},
+        {"time": 2.0, "velocity": 1.0, "acceleration": 0.0, "pose": {"translation": {"x": 1.5, "y": 0.0}, "rotation": {"radians": 0.5}}, "curvature": 0.0}
+    ]"#;
+    let path = Path::from_wpilib(data).unwrap();
+
+    let pose = path.get(uom::si::f64::Time::new::<uom::si::time::second>(1.0));
+    assert_eq!(pose.angular_velocity.get::<radian_per_second>(), 0.5);
+
+    let states = serde_json::from_str::<Vec<WpilibState>>(&path.to_wpilib().unwrap()).unwrap();
+    let original = serde_json::from_str::<Vec<WpilibState>>(data).unwrap();
+    assert_eq!(states.len(), original.len());
+    for (state, original) in states.iter().zip(&original) {
+        assert!((state.velocity - original.velocity).abs() < 1e-9);
+        assert!((state.curvature - original.curvature).abs() < 1e-9);
+        assert_eq!(state.pose, original.pose);
+    }
+    assert_eq!(states[0].acceleration, 1.0);
+    assert!((states[1].acceleration - original[1].acceleration).abs() < 1e-9);
+}